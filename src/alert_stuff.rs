@@ -0,0 +1,503 @@
+//! Alert rules over objectives and habits and the outputs fired alerts are sent to
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config_stuff::Config;
+use crate::data_stuff::HabitDayPerformance;
+use crate::schedule_stuff::{
+    habit_statuses, last_day, lifecycle, weekly_shortfalls, HabitStatus, Schedule,
+};
+
+/// What has to hold on a day for it to count towards a rule
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    ObjectiveAbove {
+        objective: String,
+        value: f32,
+    },
+    ObjectiveBelow {
+        objective: String,
+        value: f32,
+    },
+    /// FAIL or no entry on a day the habit's schedule expects it. A per week habit counts the
+    /// ticks its finished weeks fell short by instead of days.
+    HabitMissed {
+        habit: String,
+    },
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+fn one_day() -> u32 {
+    1
+}
+
+/// Fires when `when` holds for `days` days in a row up to the latest day in the notes
+///
+/// ```toml
+/// [[alerts]]
+/// name = "GERD flare"
+/// when = { kind = "objective_above", objective = "GERD-Symptoms(0-10)=", value = 6.0 }
+/// days = 3
+/// severity = "warning"
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: Option<String>,
+    pub when: Condition,
+    #[serde(default = "one_day")]
+    pub days: u32,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+impl Rule {
+    /// Rejects a rule watching a title that isn't a known habit or objective once aliases are
+    /// followed, it could never fire
+    pub fn validate(&self, config: &Config) -> Result<(), String> {
+        let (title, known, kind) = match &self.when {
+            Condition::HabitMissed { habit } => (habit, config.known_habits(), "habit"),
+            Condition::ObjectiveAbove { objective, .. }
+            | Condition::ObjectiveBelow { objective, .. } => {
+                (objective, config.known_objectives(), "objective")
+            }
+        };
+        match known.contains(&config.canonical_title(title).as_str()) {
+            true => Ok(()),
+            false => Err(format!(
+                "alert {} watches {title}, which isn't a known {kind} title",
+                self.describe()
+            )),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (&self.name, &self.when) {
+            (Some(name), _) => name.clone(),
+            (None, Condition::ObjectiveAbove { objective, value }) => {
+                format!("{objective} above {value}")
+            }
+            (None, Condition::ObjectiveBelow { objective, value }) => {
+                format!("{objective} below {value}")
+            }
+            (None, Condition::HabitMissed { habit }) => format!("{habit} missed"),
+        }
+    }
+}
+
+/// Where fired alerts go
+///
+/// ```toml
+/// [alert_outputs]
+/// stdout = true
+/// file = "/home/test/alerts.log"
+/// command = ["notify-send", "Health mode"]
+/// webhook = "http://127.0.0.1:9000/alerts"
+/// ```
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertOutputs {
+    pub stdout: bool,
+    /// Appended to, one line per alert
+    pub file: Option<PathBuf>,
+    /// Run once per alert with the message as the last argument and the alert as JSON in
+    /// `HEALTH_MODE_ALERT`
+    pub command: Option<Vec<String>>,
+    /// Plain `http://` URL that gets all alerts POSTed as a JSON array
+    pub webhook: Option<String>,
+}
+
+impl Default for AlertOutputs {
+    fn default() -> Self {
+        AlertOutputs {
+            stdout: true,
+            file: None,
+            command: None,
+            webhook: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub rule: String,
+    pub severity: Severity,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: u32,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} for {} days ({} to {})",
+            self.severity, self.rule, self.days, self.start, self.end
+        )
+    }
+}
+
+/// Length and first day of the run of matching days that ends on the latest day.
+/// A day without a file breaks the run, days `matches` returns `None` for are stepped over.
+fn trailing_run(
+    hdps: &[HabitDayPerformance],
+    matches: impl Fn(&HabitDayPerformance) -> Option<bool>,
+) -> Option<(NaiveDate, u32)> {
+    let mut run: Option<(NaiveDate, u32)> = None;
+    let mut next_date: Option<NaiveDate> = None;
+    for hdp in hdps.iter().rev() {
+        let date = hdp.date();
+        if let Some(next) = next_date {
+            if (next - date).num_days() > 1 {
+                break;
+            }
+        }
+        next_date = Some(date);
+        match matches(hdp) {
+            Some(true) => {
+                let days = run.map(|(_, d)| d).unwrap_or(0) + 1;
+                run = Some((date, days));
+            }
+            Some(false) => break,
+            None => continue,
+        }
+    }
+    run
+}
+
+/// Missing ticks and first Monday of the run of short weeks that ends on the latest finished
+/// week, a week without any day file breaks the run
+fn trailing_weeks(shortfalls: &[(NaiveDate, u32)]) -> Option<(NaiveDate, u32)> {
+    let mut run: Option<(NaiveDate, u32)> = None;
+    let mut next_monday: Option<NaiveDate> = None;
+    for (monday, missing) in shortfalls.iter().rev() {
+        if next_monday.is_some_and(|next| (next - *monday).num_days() > 7) || *missing == 0 {
+            break;
+        }
+        next_monday = Some(*monday);
+        let total = run.map(|(_, m)| m).unwrap_or(0) + missing;
+        run = Some((*monday, total));
+    }
+    run
+}
+
+/// Checks every rule against the notes, returning the ones that are firing now
+pub fn evaluate(rules: &[Rule], hdps: &[HabitDayPerformance], config: &Config) -> Vec<Alert> {
+    let end = match hdps.last() {
+        Some(last) => last.date(),
+        None => return vec![],
+    };
+
+    rules
+        .iter()
+        .filter_map(|rule| {
+            let run = match &rule.when {
+                Condition::ObjectiveAbove { objective, value } => {
+                    let objective = &config.canonical_title(objective);
+                    let aggregate = config.aggregate(objective);
+                    trailing_run(hdps, |h| {
+                        Some(h.objective_by(objective, aggregate)? > *value)
+                    })
+                }
+                Condition::ObjectiveBelow { objective, value } => {
+                    let objective = &config.canonical_title(objective);
+                    let aggregate = config.aggregate(objective);
+                    trailing_run(hdps, |h| {
+                        Some(h.objective_by(objective, aggregate)? < *value)
+                    })
+                }
+                Condition::HabitMissed { habit } => {
+                    let habit = &config.canonical_title(habit);
+                    let lifecycle = lifecycle(hdps, habit, config);
+                    match config.schedule(habit) {
+                        // A weekly target can only be missed once its week is over
+                        Schedule::PerWeek { times } => trailing_weeks(&weekly_shortfalls(
+                            hdps,
+                            habit,
+                            times,
+                            lifecycle,
+                            last_day(hdps),
+                        )),
+                        schedule => {
+                            let statuses =
+                                habit_statuses(hdps, habit, &schedule, lifecycle, last_day(hdps));
+                            trailing_run(hdps, |h| match statuses[&h.date()] {
+                                HabitStatus::Missed | HabitStatus::Unrecorded => Some(true),
+                                HabitStatus::NotScheduled => None,
+                                _ => Some(false),
+                            })
+                        }
+                    }
+                }
+            };
+            let (start, days) = run?;
+            if days < rule.days.max(1) {
+                return None;
+            }
+            Some(Alert {
+                rule: rule.describe(),
+                severity: rule.severity,
+                start,
+                end,
+                days,
+            })
+        })
+        .collect()
+}
+
+/// POSTs `body` to a plain http URL, enough for a hook listening on this machine
+fn post_json(url: &str, body: &str) -> Result<(), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or(format!("{url}: only http:// webhooks are supported"))?;
+    let (host, path) = match rest.split_once('/') {
+        Some((host, path)) => (host, format!("/{path}")),
+        None => (rest, "/".to_string()),
+    };
+    let address = match host.contains(':') {
+        true => host.to_string(),
+        false => format!("{host}:80"),
+    };
+
+    let mut stream = TcpStream::connect(&address).map_err(|e| format!("{url}: {e}"))?;
+    let request = format!(
+        "POST {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("{url}: {e}"))?;
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|e| format!("{url}: {e}"))?;
+    let status = response.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(format!("{url}: webhook answered {status}"));
+    }
+    Ok(())
+}
+
+/// Sends alerts to every configured output
+pub fn dispatch(alerts: &[Alert], outputs: &AlertOutputs) -> Result<(), String> {
+    if alerts.is_empty() {
+        return Ok(());
+    }
+
+    if outputs.stdout {
+        for alert in alerts {
+            println!("{alert}");
+        }
+    }
+
+    if let Some(path) = &outputs.file {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        for alert in alerts {
+            writeln!(file, "{alert}").map_err(|e| format!("{}: {e}", path.display()))?;
+        }
+    }
+
+    if let Some(command) = &outputs.command {
+        let (program, args) = command
+            .split_first()
+            .ok_or("alert command is empty".to_string())?;
+        for alert in alerts {
+            let json = serde_json::to_string(alert).map_err(|e| e.to_string())?;
+            let status = Command::new(program)
+                .args(args)
+                .arg(alert.to_string())
+                .env("HEALTH_MODE_ALERT", json)
+                .status()
+                .map_err(|e| format!("{program}: {e}"))?;
+            if !status.success() {
+                return Err(format!("{program} exited with {status}"));
+            }
+        }
+    }
+
+    if let Some(url) = &outputs.webhook {
+        let json = serde_json::to_string(alerts).map_err(|e| e.to_string())?;
+        post_json(url, &json)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_stuff::{Performance, Task, TaskPerformance};
+    use chrono::Days;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    /// A day with the GERD score and Walk entry it's given
+    fn day(d: &str, gerd: Option<f32>, walk: Option<Performance>) -> HabitDayPerformance {
+        let mut hdp = HabitDayPerformance::new(date(d));
+        if let Some(gerd) = gerd {
+            let task = Task::Objective("GERD-Symptoms(0-10)=".to_string());
+            hdp.objective_performance
+                .push(TaskPerformance::new(task, Performance::Score(gerd)));
+        }
+        if let Some(walk) = walk {
+            let task = Task::Todo("#1 Walk".to_string());
+            hdp.todo_performance.push(TaskPerformance::new(task, walk));
+        }
+        hdp
+    }
+
+    fn config(alerts: &str) -> Config {
+        toml::from_str(alerts).unwrap()
+    }
+
+    #[test]
+    fn rules_must_watch_known_titles() {
+        let config = config(
+            r#"
+            [[alerts]]
+            name = "GERD flare"
+            when = { kind = "objective_above", objective = "GERD-Symptoms(0-10)=", value = 6.0 }
+            days = 3
+            severity = "warning"
+
+            [[alerts]]
+            when = { kind = "habit_missed", habit = "Juggle" }
+            "#,
+        );
+        assert_eq!(config.alerts[0].days, 3);
+        assert_eq!(config.alerts[1].days, 1);
+        assert!(config.alerts[0].validate(&config).is_ok());
+        assert_eq!(
+            config.validate().unwrap_err(),
+            "alert Juggle missed watches Juggle, which isn't a known habit title"
+        );
+    }
+
+    #[test]
+    fn objective_rules_fire_on_a_trailing_run() {
+        let config = config(
+            r#"
+            [[alerts]]
+            name = "GERD flare"
+            when = { kind = "objective_above", objective = "GERD-Symptoms(0-10)=", value = 6.0 }
+            days = 3
+            severity = "warning"
+            "#,
+        );
+        let hdps = vec![
+            day("2025-03-01", Some(7.0), None),
+            day("2025-03-02", Some(3.0), None),
+            day("2025-03-03", Some(7.0), None),
+            day("2025-03-04", None, None),
+            day("2025-03-05", Some(8.0), None),
+            day("2025-03-06", Some(9.0), None),
+        ];
+        let alerts = evaluate(&config.alerts, &hdps, &config);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0].to_string(),
+            "[warning] GERD flare for 3 days (2025-03-03 to 2025-03-06)"
+        );
+
+        // A day without a file breaks the run
+        let gap = [&hdps[..3], &hdps[4..]].concat();
+        assert!(evaluate(&config.alerts, &gap, &config).is_empty());
+    }
+
+    #[test]
+    fn habit_rules_follow_the_schedule() {
+        use Performance::{Done, Fail};
+        let daily = config(
+            r##"
+            [[alerts]]
+            when = { kind = "habit_missed", habit = "#1 Walk" }
+            days = 2
+            "##,
+        );
+        let hdps = vec![
+            day("2025-03-01", None, Some(Done)),
+            day("2025-03-02", None, Some(Fail)),
+            day("2025-03-03", None, None),
+        ];
+        let alerts = evaluate(&daily.alerts, &hdps, &daily);
+        assert_eq!(
+            alerts[0].to_string(),
+            "[info] #1 Walk missed for 2 days (2025-03-02 to 2025-03-03)"
+        );
+
+        let weekly = config(
+            r##"
+            [habits."#1 Walk"]
+            schedule = { kind = "per_week", times = 2 }
+
+            [[alerts]]
+            when = { kind = "habit_missed", habit = "#1 Walk" }
+            days = 3
+            "##,
+        );
+        // Two finished weeks from Monday 2025-03-03 with a single walk, then a running week
+        let hdps: Vec<HabitDayPerformance> = (0..15)
+            .map(|i| {
+                let d = date("2025-03-03") + Days::new(i);
+                let walk = (i == 1).then_some(Done);
+                day(&d.to_string(), None, walk)
+            })
+            .collect();
+        let alerts = evaluate(&weekly.alerts, &hdps, &weekly);
+        assert_eq!((alerts[0].start, alerts[0].days), (date("2025-03-03"), 3));
+    }
+
+    #[test]
+    fn alerts_are_appended_to_the_file_output() {
+        let path = std::env::temp_dir().join("org_analyzer_alerts_test.log");
+        let _ = std::fs::remove_file(&path);
+        let outputs = AlertOutputs {
+            stdout: false,
+            file: Some(path.clone()),
+            ..AlertOutputs::default()
+        };
+        let alerts = [Alert {
+            rule: "Walk missed".to_string(),
+            severity: Severity::Critical,
+            start: date("2025-03-01"),
+            end: date("2025-03-02"),
+            days: 2,
+        }];
+        dispatch(&alerts, &outputs).unwrap();
+        dispatch(&alerts, &outputs).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written.lines().count(), 2);
+        assert!(written.starts_with("[critical] Walk missed for 2 days"));
+    }
+}
@@ -1,191 +1,428 @@
-use crate::data_stuff::{collect_org_data, HabitDayPerformance, HabitProgress};
+//! The [`Analyzer`] that turns habit data into charts, stats and reviews
+
+use crate::alert_stuff::{dispatch, evaluate};
+use crate::clock_stuff::{habit_times, HabitTimes};
+use crate::config_stuff::Config;
+use crate::data_stuff::{Aggregate, HabitDayPerformance, HabitProgress};
 use crate::draw_stuff::heatmap::draw_grid_lines;
 use crate::draw_stuff::linechart;
-use crate::time_stuff::{day_of_year, days_in_month};
-
-use crate::draw_stuff::heatmap::{build_chart, draw_rect, style_chart};
+use crate::draw_stuff::timeofday;
+use crate::draw_stuff::weekday;
+use crate::goal_stuff::{goal_progress, GoalProgress};
+use crate::query_stuff::{segments, DayFilter};
+use crate::schedule_stuff::{
+    group_grid, group_stats, habit_stats, last_day, lifecycle, status_grid, HabitStats, StatusGrid,
+};
+use crate::source_stuff::HabitSource;
+use crate::theme_stuff::Theme;
+use crate::time_stuff::{time_frames, DateSpan, FrameKind, TimeFrame};
+use crate::unit_stuff::{Quantity, Unit};
+use crate::weekday_stuff::{weekday_rates, weekday_values, WeekdayRates};
+use crate::write_stuff::WeeklyReview;
+
+use crate::draw_stuff::heatmap::{
+    build_chart, canvas_size, draw_column_labels, draw_legend, draw_rect, style_chart,
+    LEGEND_HEIGHT,
+};
+use chrono::{Days, NaiveDate};
 use plotters::coord::Shift;
 use plotters::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Habit stats and objective means with their day counts over some days
+type DaySummary = (Vec<HabitStats>, Vec<(&'static str, Option<f32>, usize)>);
+
+/// Pixel size of objective line charts unless the theme says otherwise
+pub const OBJECTIVE_SIZE: (u32, u32) = (1800, 1400);
+
+/// Longest custom range a label can ask for, a year of heatmap rows
+pub const MAX_RANGE_DAYS: u32 = 366;
+
+/// Pixel size of habit time of day charts
+pub const TIME_OF_DAY_SIZE: (u32, u32) = (1400, 1000);
+
+/// Pixel size of weekday charts
+pub const WEEKDAY_SIZE: (u32, u32) = (1400, 1000);
+
+/// Pixel size of the grid with a small chart of every objective
+pub const OBJECTIVE_GRID_SIZE: (u32, u32) = (1800, 1400);
+
+/// Objective title, axis title, y range, filename key and the unit the y range is in, which
+/// is also the unit values written without one are taken to be in
+type ObjectiveChart = (
+    &'static str,
+    &'static str,
+    (f32, f32),
+    &'static str,
+    Option<Unit>,
+);
+
+const OBJECTIVE_CHARTS: [ObjectiveChart; 3] = [
+    ("Weight=", "Weight", (50.0, 100.0), "weight", Some(Unit::Kg)),
+    (
+        "GERD-Symptoms(0-10)=",
+        "GERD Symptoms",
+        (0.0, 10.0),
+        "gerd",
+        None,
+    ),
+    ("Mood(0-10)=", "Mood", (0.0, 10.0), "mood", None),
+];
+
+/// Entry of [`OBJECTIVE_CHARTS`] with the filename key `key`
+fn objective_chart(key: &str) -> Result<ObjectiveChart, String> {
+    OBJECTIVE_CHARTS
+        .into_iter()
+        .find(|(_, _, _, k, _)| *k == key)
+        .ok_or(format!("unknown objective {key}"))
+}
+
+/// Two objectives drawn on one chart, written with their filename keys as `weight:gerd`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Overlay {
+    /// Key of the objective on the left axis
+    pub left: String,
+    /// Key of the objective on the right axis
+    pub right: String,
+}
+
+impl FromStr for Overlay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (left, right) = s
+            .split_once(':')
+            .ok_or(format!("overlay {s} should look like weight:gerd"))?;
+        objective_chart(left)?;
+        objective_chart(right)?;
+        Ok(Overlay {
+            left: left.to_string(),
+            right: right.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.left, self.right)
+    }
+}
 
-pub enum TimeFrame<T> {
-    Month(T),
-    Year(T),
+/// Unit the built in chart of `objective` is drawn in
+fn chart_unit(objective: &str) -> Option<Unit> {
+    OBJECTIVE_CHARTS
+        .into_iter()
+        .find(|(o, _, _, _, _)| *o == objective)
+        .and_then(|(_, _, _, _, unit)| unit)
 }
 
+#[derive(Debug, PartialEq, Serialize)]
 pub struct TodoTimeFrameSettings<'a> {
-    pub tf_name: String,
     pub filename: String,
     pub doc_title: String,
     pub column_titles: Vec<&'a str>,
+    pub grouped: bool,
+    pub span: DateSpan,
     pub y_axis_size: u32,
     pub x_axis_size: u32,
-    pub y_offset: u32,
+    pub theme: Theme,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct ObjTimeFrameSettings {
     pub tf_name: String,
     pub filename: String,
     pub doc_title: String,
-    pub month_name: Option<String>,
     pub y_title: String,
     pub y_range: (f32, f32),
-    pub x_axis_size: u32,
-    pub x_offset: u32,
+    pub span: DateSpan,
+    pub goal: Option<GoalProgress>,
+    pub aggregate: Aggregate,
+    pub theme: Theme,
 }
 
+#[derive(Debug, PartialEq)]
 pub struct Analyzer {
     org_data: HabitProgress,
+    config: Config,
+    filter: DayFilter,
+    theme: Theme,
 }
 
 impl Analyzer {
-    pub fn new() -> Self {
-        let org_data = collect_org_data();
-        Analyzer { org_data }
+    pub fn from_source(source: &dyn HabitSource) -> Self {
+        Analyzer::from_progress(source.load_all())
     }
 
-    fn get_monthly_objective_settings(
-        &self,
-        y_title: String,
-        y_range: (f32, f32),
-        tf_name: String,
-        filename_pre: String,
-    ) -> Vec<TimeFrame<ObjTimeFrameSettings>> {
-        let mut settings = Vec::<TimeFrame<ObjTimeFrameSettings>>::new();
-        let org_data = &self.org_data;
-        let doc_titles = org_data.doc_titles();
-        for doc_title in doc_titles.iter() {
-            let (year, month) = doc_title;
-            let days_in_month = days_in_month(month);
-            let x_axis_size = days_in_month.last().unwrap().to_owned();
-            let oldest_hdp = org_data.oldest_month_hdp(month.to_owned());
-            let x_offset = oldest_hdp.day.parse::<u32>().unwrap();
-            let doc_title = format!("{month} {year}");
-            let filename = format!("{filename_pre}-{month}-{year}.png");
-
-            let tf = ObjTimeFrameSettings {
-                tf_name: tf_name.clone(),
-                filename,
-                doc_title,
-                month_name: Some(month.to_string()),
-                y_title: y_title.clone(),
-                y_range,
-                x_axis_size,
-                x_offset,
-            };
-
-            settings.push(TimeFrame::Month(tf));
+    pub fn from_progress(org_data: HabitProgress) -> Self {
+        Analyzer {
+            org_data,
+            config: Config::default(),
+            filter: DayFilter::default(),
+            theme: Theme::default(),
+        }
+    }
+
+    /// Uses `config` from here on, moving the data onto its canonical titles and objective
+    /// values into their display units
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.org_data
+            .rename_titles(|title| config.canonical_title(title));
+        self.org_data
+            .convert_objectives(|title| config.unit(title).or(chart_unit(title)), chart_unit);
+        self.theme = config.theme.clone();
+        self.config = config;
+        self
+    }
+
+    pub fn progress(&self) -> &HabitProgress {
+        &self.org_data
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Habits a new day file for `date` lists, the built in and configured titles whose
+    /// lifecycle is active on it. Without any notes yet every one of them is listed.
+    pub fn day_habits(&self, date: NaiveDate) -> Vec<&str> {
+        let hdps = self.org_data.hpds();
+        self.config
+            .known_habits()
+            .into_iter()
+            .filter(|title| hdps.is_empty() || lifecycle(hdps, title, &self.config).is_active(date))
+            .collect()
+    }
+
+    /// Draws the charts with `theme` instead of the configured one
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.set_theme(theme);
+        self
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Narrows the charts and stats down to the days `filter` lets through. Schedules,
+    /// lifecycles, goals, alerts and reviews still see every day.
+    pub fn with_filter(mut self, filter: DayFilter) -> Self {
+        self.set_filter(filter);
+        self
+    }
+
+    pub fn set_filter(&mut self, filter: DayFilter) {
+        self.filter = filter;
+    }
+
+    pub fn filter(&self) -> &DayFilter {
+        &self.filter
+    }
+
+    /// Days the charts and stats look at, every day unless there is a filter
+    pub fn days(&self) -> Vec<HabitDayPerformance> {
+        self.org_data.query(&self.filter)
+    }
+
+    /// Last recorded day of the whole history, weeks before it are over whatever the filter
+    fn as_of(&self) -> NaiveDate {
+        last_day(self.org_data.hpds())
+    }
+
+    fn days_in(&self, span: &DateSpan) -> Vec<HabitDayPerformance> {
+        let mut days = self.days();
+        days.retain(|hdp| span.contains(hdp.date()));
+        days
+    }
+
+    /// `title` with the filter added when there is one, e.g. `March 2025 (travel)`
+    fn filtered_title(&self, title: String) -> String {
+        match self.filter.is_empty() {
+            true => title,
+            false => format!("{title} ({})", self.filter),
+        }
+    }
+
+    /// `{stem}-{filter}.png` when there is a filter, so filtered charts don't overwrite the others
+    fn filtered_filename(&self, stem: String) -> String {
+        match self.filter.is_empty() {
+            true => format!("{stem}.png"),
+            false => format!("{stem}-{}.png", self.filter.file_label()),
+        }
+    }
+
+    /// Time frame whose label is `label`, e.g. 2025-W14, 2025-March, 2025-Q1, 2025 or
+    /// 2025-03-01_2025-03-31 for a custom range of up to [`MAX_RANGE_DAYS`]
+    pub fn frame_by_label(&self, label: &str) -> Option<TimeFrame<DateSpan>> {
+        if let Some((from, to)) = label.split_once('_') {
+            let span = DateSpan::new(from.parse().ok()?, to.parse().ok()?);
+            let days = span.days();
+            return (days > 0 && days <= MAX_RANGE_DAYS).then_some(TimeFrame::Range(span));
+        }
+        [
+            FrameKind::Week,
+            FrameKind::Month,
+            FrameKind::Quarter,
+            FrameKind::Year,
+        ]
+        .into_iter()
+        .flat_map(|kind| self.frames(kind))
+        .find(|frame| frame.label() == label)
+    }
+
+    /// Every calendar time frame of `kind` the data touches
+    pub fn frames(&self, kind: FrameKind) -> Vec<TimeFrame<DateSpan>> {
+        match self.org_data.date_span() {
+            Some(span) => time_frames(kind, span.start, span.end),
+            None => vec![],
         }
-        settings
     }
 
-    fn get_yearly_objective_settings(
+    fn objective_settings(
         &self,
+        frame: &TimeFrame<DateSpan>,
         y_title: String,
         y_range: (f32, f32),
         tf_name: String,
-        filename: String,
+        filename_key: &str,
+        base_unit: Option<Unit>,
     ) -> TimeFrame<ObjTimeFrameSettings> {
-        let org_data = &self.org_data;
-        let doc_title = y_title.clone();
-        let todays_progress = org_data.get_current_hdp();
-        let oldest_progress = org_data.get_oldest_hdp();
-        let doy_offset = day_of_year(&oldest_progress);
-        let day_of_year_current_hdp = day_of_year(&todays_progress);
-        let x_axis_size = day_of_year_current_hdp;
-        let x_offset = doy_offset;
-
-        let tf = ObjTimeFrameSettings {
+        let unit = self.config.unit(&tf_name).or(base_unit.clone());
+        let y_range = match (base_unit, &unit) {
+            (Some(base), Some(unit)) => {
+                let convert = |value| {
+                    Quantity {
+                        value,
+                        unit: base.clone(),
+                    }
+                    .convert(unit)
+                };
+                match (convert(y_range.0), convert(y_range.1)) {
+                    (Some(min), Some(max)) => (min.value, max.value),
+                    _ => y_range,
+                }
+            }
+            _ => y_range,
+        };
+        let y_title = match unit {
+            Some(unit) => format!("{y_title} ({unit})"),
+            None => y_title,
+        };
+        let label = frame.label();
+        let doc_title = self.filtered_title(format!("{y_title} {}", frame.doc_title()));
+        let filename = self.filtered_filename(format!(
+            "{}_objective_performance_{filename_key}-{label}",
+            frame.name()
+        ));
+
+        let goal = self.goal_progress(&tf_name);
+        let aggregate = self.config.aggregate(&tf_name);
+
+        frame.map(|span| ObjTimeFrameSettings {
             tf_name,
             filename,
             doc_title,
-            month_name: None,
             y_title,
             y_range,
-            x_axis_size,
-            x_offset,
-        };
-        TimeFrame::Year(tf)
+            span: *span,
+            goal,
+            aggregate,
+            theme: self.theme.clone(),
+        })
     }
 
-    fn get_yearly_settings<'a>(&self) -> TimeFrame<TodoTimeFrameSettings<'a>> {
-        let org_data = &self.org_data;
-        let column_titles = HabitProgress::ordered_titles();
-        let todays_progress = org_data.get_current_hdp();
-        let oldest_progress = org_data.get_oldest_hdp();
-        let doy_offset = day_of_year(&oldest_progress);
-        let day_of_year_current_hdp = day_of_year(&todays_progress);
-        let x_axis_size = (column_titles.len() - 1) as u32;
-        let y_axis_size = day_of_year_current_hdp;
-        let y_offset = doy_offset;
-        let filename = "yearly_habit_performance.png".to_string();
+    /// Settings of the objective chart with the filename key `key`
+    fn objective_settings_by_key(
+        &self,
+        frame: &TimeFrame<DateSpan>,
+        key: &str,
+    ) -> Result<TimeFrame<ObjTimeFrameSettings>, String> {
+        let (tf_name, y_title, y_range, filename_key, unit) = objective_chart(key)?;
+        Ok(self.objective_settings(
+            frame,
+            y_title.to_string(),
+            y_range,
+            tf_name.to_string(),
+            filename_key,
+            unit,
+        ))
+    }
 
-        let tf = TodoTimeFrameSettings {
-            tf_name: "2025".to_string(),
-            filename,
-            doc_title: "2025".to_string(),
-            column_titles,
-            y_axis_size,
-            x_axis_size,
-            y_offset,
-        };
-        TimeFrame::Year(tf)
+    fn column_titles<'a>(grouped: bool) -> Vec<&'a str> {
+        if grouped {
+            HabitProgress::ordered_habit_names()
+        } else {
+            HabitProgress::ordered_titles()
+        }
     }
 
-    fn get_monthly_settings<'a>(&self) -> Vec<TimeFrame<TodoTimeFrameSettings<'a>>> {
-        let mut settings = Vec::<TimeFrame<TodoTimeFrameSettings<'a>>>::new();
-        let org_data = &self.org_data;
-        let doc_titles = org_data.doc_titles();
-        let column_titles = HabitProgress::ordered_titles();
+    fn todo_settings<'a>(
+        &self,
+        frame: &TimeFrame<DateSpan>,
+        grouped: bool,
+    ) -> TimeFrame<TodoTimeFrameSettings<'a>> {
+        let column_titles = Analyzer::column_titles(grouped);
         let x_axis_size = (column_titles.len() - 1) as u32;
-        for doc_title in doc_titles.iter() {
-            let (year, month) = doc_title;
-            let days_in_month = days_in_month(month);
-            let y_axis_size = days_in_month.last().unwrap().to_owned();
-            let oldest_hdp = org_data.oldest_month_hdp(month.to_owned());
-            let y_offset = oldest_hdp.day.parse::<u32>().unwrap();
-            let filename = format!("{year}-{month}.png");
-            let doc_title = format!("{month} {year}");
-
-            println!("monthly\ny axis size: {y_axis_size}, x_axis_size: {x_axis_size}");
-            let tf = TodoTimeFrameSettings {
-                tf_name: month.to_owned(),
-                filename,
-                doc_title,
-                column_titles: column_titles.clone(),
-                y_axis_size,
-                x_axis_size,
-                y_offset,
-            };
-
-            settings.push(TimeFrame::Month(tf));
-        }
-        settings
+        let label = frame.label();
+        let filename = self.filtered_filename(match grouped {
+            true => format!("{}_habit_group_performance-{label}", frame.name()),
+            false => format!("{}_habit_performance-{label}", frame.name()),
+        });
+        let doc_title = self.filtered_title(frame.doc_title());
+
+        frame.map(|span| TodoTimeFrameSettings {
+            filename,
+            doc_title,
+            column_titles,
+            grouped,
+            span: *span,
+            y_axis_size: span.days(),
+            x_axis_size,
+            theme: self.theme.clone(),
+        })
     }
 
-    fn draw_todos(&self, settings: &TimeFrame<TodoTimeFrameSettings>) {
-        let (hdps, filename): (&Vec<HabitDayPerformance>, String) = {
-            match &settings {
-                TimeFrame::Year(s) => (self.org_data.hpds(), s.filename.clone()),
-                TimeFrame::Month(s) => (
-                    &self.org_data.filter_by_month(s.tf_name.clone()),
-                    s.filename.clone(),
-                ),
-            }
+    fn draw_todos<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        settings: &TimeFrame<TodoTimeFrameSettings>,
+    ) {
+        let s = settings.inner();
+        let hdps = self.days_in(&s.span);
+        root.fill(&self.theme.colours().background).unwrap();
+        let height = root.dim_in_pixel().1;
+        let (root, legend) = root.split_vertically(height.saturating_sub(LEGEND_HEIGHT));
+        let root = &root;
+
+        // Set chart type, a span without days leaves the canvas blank
+        let mut chart = match build_chart(root, settings) {
+            Ok(chart) => chart,
+            Err(_) => return,
         };
-        println!("{filename}");
-        // Draw, Size, Style Canvas
-        let root: DrawingArea<BitMapBackend, Shift> =
-            BitMapBackend::new(&filename, (1000, 1000)).into_drawing_area();
-        root.fill(&WHITE).unwrap();
-
-        // Set chart type
-        let mut chart = build_chart(&root, settings).unwrap();
 
         // Set chart style
         chart = style_chart(chart, settings).unwrap();
+        draw_column_labels(root, &chart, settings);
+        draw_legend(&legend, &self.theme, s.grouped);
 
         // Draw org data onto chart
-        chart = draw_rect(chart, hdps, settings);
+        // Statuses come from the whole history so weekly targets see full weeks
+        let statuses = match s.grouped {
+            true => group_grid(
+                self.org_data.hpds(),
+                &s.column_titles,
+                &HabitProgress::ordered_titles(),
+                &self.config,
+            ),
+            false => status_grid(self.org_data.hpds(), &s.column_titles, &self.config),
+        };
+        chart = draw_rect(chart, &hdps, &statuses, settings);
 
         // Draw custom gridlines
         let _chart = draw_grid_lines(chart, settings);
@@ -193,34 +430,431 @@ impl Analyzer {
         root.present().unwrap();
     }
 
-    pub fn monthly_habit_performance(&self) -> Result<String, String> {
-        let settings = &self.get_monthly_settings();
-        for tf in settings.iter() {
-            let _ = &self.draw_todos(tf);
+    /// Pixel size of the habit heatmap for `frame`, see [`canvas_size`]
+    pub fn heatmap_size(&self, frame: &TimeFrame<DateSpan>, grouped: bool) -> (u32, u32) {
+        let titles = Analyzer::column_titles(grouped);
+        canvas_size(&titles, frame.inner().days(), &self.theme)
+    }
+
+    /// Habit heatmap for `frame` drawn onto any plotters backend, sized by
+    /// [`Analyzer::heatmap_size`]
+    pub fn draw_habits_on<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        frame: &TimeFrame<DateSpan>,
+        grouped: bool,
+    ) {
+        let settings = self.todo_settings(frame, grouped);
+        self.draw_todos(root, &settings);
+    }
+
+    pub fn habit_performance(
+        &self,
+        frames: &[TimeFrame<DateSpan>],
+        grouped: bool,
+    ) -> Result<String, String> {
+        for frame in frames {
+            let settings = self.todo_settings(frame, grouped);
+            let filename = settings.inner().filename.clone();
+            println!("{filename}");
+            let size = self.heatmap_size(frame, grouped);
+            let root = BitMapBackend::new(&filename, size).into_drawing_area();
+            self.draw_todos(&root, &settings);
+        }
+        match grouped {
+            true => Ok(format!("completed {} habit group heatmaps", frames.len())),
+            false => Ok(format!("completed {} habit heatmaps", frames.len())),
+        }
+    }
+
+    pub fn habit_completion(&self) -> Result<String, String> {
+        let mut report = self.filtered_title(String::from("habit completion on scheduled days"));
+        for title in HabitProgress::ordered_titles() {
+            let stats = self.habit_stats(title);
+            report.push_str(&format!("\n  {stats}"));
+        }
+
+        let names = HabitProgress::ordered_habit_names();
+        let titles = HabitProgress::ordered_titles();
+        let grid = group_grid(self.org_data.hpds(), &names, &titles, &self.config);
+        let grid = self.keep_days(grid, &self.days());
+        report.push_str("\nhabit daily fraction");
+        for stats in group_stats(&grid, &names, &titles) {
+            report.push_str(&format!("\n  {stats}"));
+        }
+        Ok(report)
+    }
+
+    /// `grid` worked out over the whole history cut down to the days in `hdps`
+    fn keep_days(&self, mut grid: StatusGrid, hdps: &[HabitDayPerformance]) -> StatusGrid {
+        let dates: Vec<NaiveDate> = hdps.iter().map(|hdp| hdp.date()).collect();
+        for days in grid.values_mut() {
+            days.retain(|date, _| dates.contains(date));
+        }
+        grid
+    }
+
+    /// Weekday completion of every habit over `hdps`
+    fn weekday_rates(&self, hdps: &[HabitDayPerformance]) -> Vec<WeekdayRates> {
+        let titles = HabitProgress::ordered_titles();
+        let grid = status_grid(self.org_data.hpds(), &titles, &self.config);
+        let grid = self.keep_days(grid, hdps);
+        titles
+            .into_iter()
+            .filter_map(|title| Some(weekday_rates(title, grid.get(title)?)))
+            .collect()
+    }
+
+    /// Best and worst weekday of every habit, of all habits together and of every objective
+    pub fn weekday_summary(&self) -> Result<String, String> {
+        let hdps = self.days();
+        let rates = self.weekday_rates(&hdps);
+        let mut report = self.filtered_title(String::from("habit completion by weekday"));
+        for r in rates.iter().filter(|r| r.best().is_some()) {
+            report.push_str(&format!("\n  {r}"));
+        }
+        let total = WeekdayRates::total("all habits", &rates);
+        report.push_str(&format!("\n  {total}"));
+        let percent =
+            |rate: Option<f32>| rate.map_or("-".to_string(), |r| format!("{:.0}%", r * 100.0));
+        let (weekdays, weekends) = total.weekend();
+        report.push_str(&format!(
+            "\n  Mon to Fri {}, weekends {}",
+            percent(weekdays),
+            percent(weekends)
+        ));
+        report.push_str("\nobjectives by weekday");
+        for objective in HabitProgress::ordered_objectives() {
+            let values = weekday_values(&hdps, objective, self.config.aggregate(objective));
+            if values.highest().is_some() {
+                report.push_str(&format!("\n  {values}"));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Weekday by habit completion chart for `frame` drawn onto any plotters backend, sized
+    /// [`WEEKDAY_SIZE`]
+    pub fn draw_weekdays_on<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        frame: &TimeFrame<DateSpan>,
+    ) {
+        let rates: Vec<WeekdayRates> = self
+            .weekday_rates(&self.days_in(frame.inner()))
+            .into_iter()
+            .filter(|r| r.best().is_some())
+            .collect();
+        let doc_title = self.filtered_title(format!("Habits by weekday {}", frame.doc_title()));
+        weekday::draw_rates(root, &doc_title, &rates, &self.theme);
+    }
+
+    /// Weekday bar chart for `frame` of the objective with the filename key `key`, sized
+    /// [`WEEKDAY_SIZE`]
+    pub fn draw_objective_weekdays_on<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        frame: &TimeFrame<DateSpan>,
+        key: &str,
+    ) -> Result<(), String> {
+        let settings = self.objective_settings_by_key(frame, key)?;
+        let s = settings.inner();
+        let values = weekday_values(&self.days_in(frame.inner()), &s.tf_name, s.aggregate);
+        let doc_title =
+            self.filtered_title(format!("{} by weekday {}", s.y_title, frame.doc_title()));
+        weekday::draw_values(
+            root,
+            &doc_title,
+            &s.y_title,
+            s.y_range,
+            &values,
+            &self.theme,
+        );
+        Ok(())
+    }
+
+    pub fn weekday_charts(&self, frames: &[TimeFrame<DateSpan>]) -> Result<String, String> {
+        for frame in frames {
+            let label = frame.label();
+            let filename =
+                self.filtered_filename(format!("{}_habit_weekdays-{label}", frame.name()));
+            let root = BitMapBackend::new(&filename, WEEKDAY_SIZE).into_drawing_area();
+            self.draw_weekdays_on(&root, frame);
+
+            for (_, _, _, key, _) in OBJECTIVE_CHARTS {
+                let filename = self.filtered_filename(format!(
+                    "{}_objective_weekdays_{key}-{label}",
+                    frame.name()
+                ));
+                let root = BitMapBackend::new(&filename, WEEKDAY_SIZE).into_drawing_area();
+                self.draw_objective_weekdays_on(&root, frame, key)?;
+            }
+        }
+        Ok(format!(
+            "completed weekday charts for {} time frames",
+            frames.len()
+        ))
+    }
+
+    /// Completion of every habit and the mean of every objective over `hdps`
+    fn day_summary(&self, hdps: &[HabitDayPerformance]) -> DaySummary {
+        let habits = HabitProgress::ordered_titles()
+            .into_iter()
+            .map(|title| {
+                let lifecycle = lifecycle(self.org_data.hpds(), title, &self.config);
+                habit_stats(
+                    hdps,
+                    title,
+                    &self.config.schedule(title),
+                    lifecycle,
+                    self.as_of(),
+                )
+            })
+            .collect();
+        let objectives = HabitProgress::ordered_objectives()
+            .into_iter()
+            .map(|objective| {
+                let aggregate = self.config.aggregate(objective);
+                let values: Vec<f32> = hdps
+                    .iter()
+                    .filter_map(|hdp| hdp.objective_by(objective, aggregate))
+                    .collect();
+                (objective, Aggregate::Mean.apply(&values), values.len())
+            })
+            .collect();
+        (habits, objectives)
+    }
+
+    /// Habit completion and objective averages over only the days tagged `tag`
+    pub fn tagged_summary(&self, tag: &str) -> Result<String, String> {
+        let hdps = self
+            .org_data
+            .query(&self.filter.and(&DayFilter::tagged(tag)));
+        if hdps.is_empty() {
+            return Err(format!("no days tagged {tag}"));
+        }
+        let (habits, objectives) = self.day_summary(&hdps);
+        let mut report = format!(
+            "habit completion on days tagged {tag} ({} days)",
+            hdps.len()
+        );
+        for stats in habits {
+            report.push_str(&format!("\n  {stats}"));
+        }
+        report.push_str(&format!("\nobjective averages on days tagged {tag}"));
+        for (objective, mean, days) in objectives {
+            if let Some(mean) = mean {
+                report.push_str(&format!("\n  {objective}: {mean:.1} over {days} days"));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Habit completion and objective averages on the days tagged `tag` next to the days
+    /// without it, e.g. GERD on travel against non travel days
+    pub fn segment_summary(&self, tag: &str) -> Result<String, String> {
+        let [(tagged_name, tagged), (rest_name, rest)] = segments(tag);
+        let tagged = self.org_data.query(&self.filter.and(&tagged));
+        let rest = self.org_data.query(&self.filter.and(&rest));
+        if tagged.is_empty() {
+            return Err(format!("no days tagged {tag}"));
+        }
+        let (tagged_habits, tagged_objectives) = self.day_summary(&tagged);
+        let (rest_habits, rest_objectives) = self.day_summary(&rest);
+
+        let mut report = format!(
+            "{tagged_name} ({} days) against {rest_name} ({} days)",
+            tagged.len(),
+            rest.len()
+        );
+        let rate = |stats: &HabitStats| match stats.expected {
+            0 => "-".to_string(),
+            _ => format!(
+                "{:.0}% ({}/{})",
+                stats.rate() * 100.0,
+                stats.done,
+                stats.expected
+            ),
+        };
+        for (a, b) in tagged_habits.iter().zip(&rest_habits) {
+            if a.expected + b.expected > 0 {
+                report.push_str(&format!("\n  {}: {} vs {}", a.title, rate(a), rate(b)));
+            }
+        }
+        let mean = |m: Option<f32>| m.map_or("-".to_string(), |m| format!("{m:.1}"));
+        for ((objective, a, _), (_, b, _)) in tagged_objectives.iter().zip(&rest_objectives) {
+            if a.is_some() || b.is_some() {
+                report.push_str(&format!("\n  {objective}: {} vs {}", mean(*a), mean(*b)));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Completion of `title` on its scheduled days over the whole history, or the filtered days
+    pub fn habit_stats(&self, title: &str) -> HabitStats {
+        let lifecycle = lifecycle(self.org_data.hpds(), title, &self.config);
+        habit_stats(
+            &self.days(),
+            title,
+            &self.config.schedule(title),
+            lifecycle,
+            self.as_of(),
+        )
+    }
+
+    /// Completion times and clocked totals of `title` over the whole history, or the filtered
+    /// days
+    pub fn habit_times(&self, title: &str) -> HabitTimes {
+        habit_times(&self.days(), title)
+    }
+
+    /// When each habit gets done and how long it was clocked, for the habits that have times
+    pub fn clocked_summary(&self) -> Result<String, String> {
+        let mut report = self.filtered_title(String::from("habit times"));
+        for title in HabitProgress::ordered_titles() {
+            let times = self.habit_times(title);
+            if !times.times.is_empty() || times.clocked > 0 {
+                report.push_str(&format!("\n  {times}"));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Time of day chart for `frame` drawn onto any plotters backend, sized
+    /// [`TIME_OF_DAY_SIZE`]
+    pub fn draw_time_of_day_on<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        frame: &TimeFrame<DateSpan>,
+    ) {
+        let hdps = self.days_in(frame.inner());
+        let habits: Vec<HabitTimes> = HabitProgress::ordered_titles()
+            .into_iter()
+            .map(|title| habit_times(&hdps, title))
+            .filter(|times| !times.times.is_empty())
+            .collect();
+        let doc_title = self.filtered_title(format!("Habit times {}", frame.doc_title()));
+        timeofday::draw_times(root, &doc_title, &habits, &self.theme);
+    }
+
+    pub fn time_of_day(&self, frames: &[TimeFrame<DateSpan>]) -> Result<String, String> {
+        for frame in frames {
+            let filename = self.filtered_filename(format!(
+                "{}_habit_time_of_day-{}",
+                frame.name(),
+                frame.label()
+            ));
+            let root = BitMapBackend::new(&filename, TIME_OF_DAY_SIZE).into_drawing_area();
+            self.draw_time_of_day_on(&root, frame);
         }
-        Ok("completed monthly analysis".to_string())
+        Ok(format!("completed {} time of day charts", frames.len()))
     }
 
-    pub fn yearly_habit_performance(&self) -> Result<String, String> {
-        let settings = &self.get_yearly_settings();
-        let _ = &self.draw_todos(settings);
+    /// Every dated value of the objective with the filename key `key`, e.g. weight
+    pub fn objective_series(&self, key: &str) -> Result<Vec<(NaiveDate, f32)>, String> {
+        let (objective, _, _, _, _) = objective_chart(key)?;
+        let aggregate = self.config.aggregate(objective);
+        Ok(self
+            .days()
+            .iter()
+            .filter_map(|hdp| Some((hdp.date(), hdp.objective_by(objective, aggregate)?)))
+            .collect())
+    }
 
-        Ok("completed yearly analysis".to_string())
+    fn goal_progress(&self, objective: &str) -> Option<GoalProgress> {
+        let goal = self.config.goal(objective)?;
+        let aggregate = self.config.aggregate(objective);
+        Some(goal_progress(
+            self.org_data.hpds(),
+            objective,
+            goal,
+            aggregate,
+            self.as_of(),
+        ))
     }
 
-    pub fn draw_objectives(&self, settings: &TimeFrame<ObjTimeFrameSettings>) {
-        let (hdps, filename): (&Vec<HabitDayPerformance>, String) = {
-            match &settings {
-                TimeFrame::Year(s) => (self.org_data.hpds(), s.filename.clone()),
-                TimeFrame::Month(s) => (
-                    &self.org_data.filter_by_month(s.month_name.clone().unwrap()),
-                    s.filename.clone(),
-                ),
+    pub fn goal_summary(&self) -> Result<String, String> {
+        let mut report = String::from("objective goals");
+        for (objective, _, _, _, _) in OBJECTIVE_CHARTS {
+            if let Some(progress) = self.goal_progress(objective) {
+                report.push_str(&format!("\n  {progress}"));
             }
+        }
+        Ok(report)
+    }
+
+    /// Checks the configured alert rules and lists the ones firing, without sending them
+    pub fn alert_summary(&self) -> Result<String, String> {
+        let alerts = evaluate(&self.config.alerts, self.org_data.hpds(), &self.config);
+        let mut summary = format!(
+            "alerts: {} of {} rules firing",
+            alerts.len(),
+            self.config.alerts.len()
+        );
+        for alert in &alerts {
+            summary.push_str(&format!("\n  {alert}"));
+        }
+        Ok(summary)
+    }
+
+    /// Checks the configured alert rules and sends the ones firing to the alert outputs
+    pub fn alerts(&self) -> Result<String, String> {
+        let alerts = evaluate(&self.config.alerts, self.org_data.hpds(), &self.config);
+        dispatch(&alerts, &self.config.alert_outputs)?;
+        Ok(format!(
+            "alerts: {} of {} rules firing",
+            alerts.len(),
+            self.config.alerts.len()
+        ))
+    }
+
+    /// Org review of the Monday to Sunday week holding `date`, returned with its label (e.g. 2025-W14)
+    pub fn weekly_review(&self, date: NaiveDate) -> Result<(String, String), String> {
+        let frame = time_frames(FrameKind::Week, date, date).remove(0);
+        let span = frame.inner();
+        let week = self.org_data.filter_by_span(span);
+        if week.is_empty() {
+            return Err(format!("no days recorded in {}", frame.label()));
+        }
+        let previous = DateSpan::new(span.start - Days::new(7), span.end - Days::new(7));
+        let previous_week = self.org_data.filter_by_span(&previous);
+
+        let habits = HabitProgress::ordered_titles()
+            .into_iter()
+            .map(|title| {
+                let lifecycle = lifecycle(self.org_data.hpds(), title, &self.config);
+                habit_stats(
+                    &week,
+                    title,
+                    &self.config.schedule(title),
+                    lifecycle,
+                    self.as_of(),
+                )
+            })
+            .collect();
+
+        let review = WeeklyReview {
+            title: frame.label(),
+            habits,
+            objectives: HabitProgress::ordered_objectives()
+                .into_iter()
+                .map(|o| (o, self.config.aggregate(o)))
+                .collect(),
+            week: &week,
+            previous_week: &previous_week,
         };
+        Ok((frame.label(), review.to_org()))
+    }
 
-        let root = BitMapBackend::new(&filename, (1800, 1400)).into_drawing_area();
-        root.fill(&WHITE).unwrap();
+    fn draw_objectives<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        settings: &TimeFrame<ObjTimeFrameSettings>,
+    ) {
+        let s = settings.inner();
+        let hdps = self.days_in(&s.span);
+
+        root.fill(&self.theme.colours().background).unwrap();
         let root = root.margin(10, 10, 10, 10);
 
         // After this point, we should be able to construct a chart context
@@ -229,69 +863,135 @@ impl Analyzer {
         // Then we can draw a mesh
         chart = linechart::style_chart(chart, settings);
 
-        let _chart = linechart::draw_data(chart, hdps, settings);
+        chart = linechart::draw_annotations(chart, &hdps, settings);
+
+        chart = linechart::draw_data(chart, &hdps, settings);
+
+        let _chart = linechart::draw_goal(chart, settings);
 
         root.present().unwrap();
     }
 
-    pub fn monthly_objective_performance(&self) -> Result<String, String> {
-        let settings = &self.get_monthly_objective_settings(
-            "Weight".to_string(),
-            (50.0, 100.0),
-            "Weight=".to_string(),
-            "monthly_objective_performance_weight".to_string(),
-        );
-        for tf in settings {
-            let _ = &self.draw_objectives(tf);
-        }
-        let g_settings = &self.get_monthly_objective_settings(
-            "GERD Symptoms".to_string(),
-            (0.0, 10.0),
-            "GERD-Symptoms(0-10)=".to_string(),
-            "monthly_objective_performance_gerd".to_string(),
-        );
-        for tf in g_settings {
-            let _ = &self.draw_objectives(tf);
-        }
-        let m_settings = &self.get_monthly_objective_settings(
-            "Mood".to_string(),
-            (0.0, 10.0),
-            "Mood(0-10)=".to_string(),
-            "monthly_objective_performance_mood".to_string(),
-        );
-        for tf in g_settings {
-            let _ = &self.draw_objectives(tf);
-        }
-        Ok("completed monthly objective report".to_string())
+    /// Objective chart for `frame` drawn onto any plotters backend, sized by the theme's
+    /// `objective_size`.
+    /// `key` is the short name used in filenames, e.g. weight.
+    pub fn draw_objective_on<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        frame: &TimeFrame<DateSpan>,
+        key: &str,
+    ) -> Result<(), String> {
+        let settings = self.objective_settings_by_key(frame, key)?;
+        self.draw_objectives(root, &settings);
+        Ok(())
     }
 
-    pub fn yearly_objective_performance(&self) -> Result<String, String> {
-        // Weight Chart
-        let settings = &self.get_yearly_objective_settings(
-            "Weight".to_string(),
-            (50.0, 100.0),
-            "Weight=".to_string(),
-            "yearly_objective_performance_weight.png".to_string(),
-        );
+    /// Objectives `overlay` names on one chart for `frame` with an axis each, sized by the
+    /// theme's `objective_size`
+    pub fn draw_overlay_on<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        frame: &TimeFrame<DateSpan>,
+        overlay: &Overlay,
+    ) -> Result<(), String> {
+        let left = self.objective_settings_by_key(frame, &overlay.left)?;
+        let right = self.objective_settings_by_key(frame, &overlay.right)?;
+        let (left, right) = (left.inner(), right.inner());
+        let doc_title = self.filtered_title(format!(
+            "{} and {} {}",
+            left.y_title,
+            right.y_title,
+            frame.doc_title()
+        ));
+
+        root.fill(&self.theme.colours().background).unwrap();
+        let root = root.margin(10, 10, 10, 10);
+        let hdps = self.days_in(frame.inner());
+        linechart::draw_overlay(&root, &doc_title, &hdps, left, right);
+        root.present().unwrap();
+        Ok(())
+    }
 
-        // Gerd Symptoms Chart
-        let g_settings = &self.get_yearly_objective_settings(
-            "GERD Symptoms".to_string(),
-            (0.0, 10.0),
-            "GERD-Symptoms(0-10)=".to_string(),
-            "yearly_objective_performance_gerd.png".to_string(),
-        );
+    /// Every objective in its own small chart for `frame`, one above the other over the same
+    /// days, sized [`OBJECTIVE_GRID_SIZE`]
+    pub fn draw_objective_grid_on<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        frame: &TimeFrame<DateSpan>,
+    ) {
+        let panels: Vec<_> = OBJECTIVE_CHARTS
+            .into_iter()
+            .map(|(tf_name, y_title, y_range, filename_key, unit)| {
+                self.objective_settings(
+                    frame,
+                    y_title.to_string(),
+                    y_range,
+                    tf_name.to_string(),
+                    filename_key,
+                    unit,
+                )
+            })
+            .collect();
+        let doc_title = self.filtered_title(format!("Objectives {}", frame.doc_title()));
+
+        root.fill(&self.theme.colours().background).unwrap();
+        let root = root.margin(10, 10, 10, 10);
+        let hdps = self.days_in(frame.inner());
+        linechart::draw_grid(&root, &doc_title, &hdps, &panels);
+        root.present().unwrap();
+    }
 
-        // Mood Chart
-        let m_settings = &self.get_yearly_objective_settings(
-            "Mood".to_string(),
-            (0.0, 10.0),
-            "Mood(0-10)=".to_string(),
-            "yearly_objective_performance_mood.png".to_string(),
-        );
-        let _ = &self.draw_objectives(settings);
-        let _ = &self.draw_objectives(g_settings);
-        let _ = &self.draw_objectives(m_settings);
-        Ok("completed yearly objective analysis".to_string())
+    /// Objective grid of every frame and a chart of every overlay in `overlays`
+    pub fn combined_charts(
+        &self,
+        frames: &[TimeFrame<DateSpan>],
+        overlays: &[Overlay],
+    ) -> Result<String, String> {
+        for frame in frames {
+            let label = frame.label();
+            let filename =
+                self.filtered_filename(format!("{}_objective_grid-{label}", frame.name()));
+            let root = BitMapBackend::new(&filename, OBJECTIVE_GRID_SIZE).into_drawing_area();
+            self.draw_objective_grid_on(&root, frame);
+
+            for overlay in overlays {
+                let filename = self.filtered_filename(format!(
+                    "{}_objective_overlay_{}_{}-{label}",
+                    frame.name(),
+                    overlay.left,
+                    overlay.right
+                ));
+                let root =
+                    BitMapBackend::new(&filename, self.theme.objective_size).into_drawing_area();
+                self.draw_overlay_on(&root, frame, overlay)?;
+            }
+        }
+        Ok(format!(
+            "completed combined objective charts for {} time frames",
+            frames.len()
+        ))
+    }
+
+    pub fn objective_performance(&self, frames: &[TimeFrame<DateSpan>]) -> Result<String, String> {
+        for frame in frames {
+            for (tf_name, y_title, y_range, filename_key, unit) in OBJECTIVE_CHARTS {
+                let settings = self.objective_settings(
+                    frame,
+                    y_title.to_string(),
+                    y_range,
+                    tf_name.to_string(),
+                    filename_key,
+                    unit,
+                );
+                let root =
+                    BitMapBackend::new(&settings.inner().filename, self.theme.objective_size)
+                        .into_drawing_area();
+                self.draw_objectives(&root, &settings);
+            }
+        }
+        Ok(format!(
+            "completed objective charts for {} time frames",
+            frames.len()
+        ))
     }
 }
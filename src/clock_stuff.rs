@@ -0,0 +1,122 @@
+//! Times of day and clocked durations read from org `CLOSED:` and `CLOCK:` lines
+
+use chrono::{NaiveDateTime, NaiveTime, Timelike};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::LazyLock;
+
+use crate::data_stuff::{HabitDayPerformance, Performance};
+
+static TIMESTAMP_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d{4}-\d{2}-\d{2})[^\d]*(\d{1,2}:\d{2})").unwrap());
+static CLOSED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"CLOSED:\s*\[([^\]]+)\]").unwrap());
+static CLOCK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"CLOCK:\s*\[([^\]]+)\]--\[([^\]]+)\]").unwrap());
+
+/// Date and time of an org timestamp body like `2025-03-14 Fri 08:12`
+fn timestamp(text: &str) -> Option<NaiveDateTime> {
+    let cap = TIMESTAMP_RE.captures(text)?;
+    let stamp = format!("{} {}", &cap[1], &cap[2]);
+    NaiveDateTime::parse_from_str(&stamp, "%Y-%m-%d %H:%M").ok()
+}
+
+/// Time of day in a `CLOSED: [2025-03-14 Fri 08:12]` line
+pub fn closed_time(line: &str) -> Option<NaiveTime> {
+    Some(timestamp(&CLOSED_RE.captures(line)?[1])?.time())
+}
+
+/// Minutes of a finished `CLOCK: [2025-03-14 Fri 07:50]--[2025-03-14 Fri 08:12] =>  0:22`
+/// line, a clock that is still running counts nothing
+pub fn clock_minutes(line: &str) -> Option<u32> {
+    let cap = CLOCK_RE.captures(line)?;
+    let minutes = (timestamp(&cap[2])? - timestamp(&cap[1])?).num_minutes();
+    u32::try_from(minutes).ok()
+}
+
+/// `1h 05m` style duration
+pub fn format_minutes(minutes: u32) -> String {
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h {:02}m", minutes % 60),
+    }
+}
+
+/// When a habit got done and how long was clocked on it over some days
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HabitTimes {
+    pub title: String,
+    /// Completion times, one per DONE day that has a `CLOSED:` time, earliest first
+    pub times: Vec<NaiveTime>,
+    /// Minutes clocked over all days
+    pub clocked: u32,
+    /// Days with any clocked time
+    pub clocked_days: u32,
+}
+
+impl HabitTimes {
+    /// Median completion time
+    pub fn typical(&self) -> Option<NaiveTime> {
+        let middle = self.times.len().checked_sub(1)? / 2;
+        match self.times.len() % 2 {
+            1 => Some(self.times[middle]),
+            _ => {
+                let seconds = |t: NaiveTime| t.num_seconds_from_midnight();
+                let mean = (seconds(self.times[middle]) + seconds(self.times[middle + 1])) / 2;
+                NaiveTime::from_num_seconds_from_midnight_opt(mean, 0)
+            }
+        }
+    }
+}
+
+impl fmt::Display for HabitTimes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.title)?;
+        match self.typical() {
+            Some(typical) => write!(
+                f,
+                "usually done at {} ({} timed)",
+                typical.format("%H:%M"),
+                self.times.len()
+            )?,
+            None => write!(f, "no completion times")?,
+        }
+        if self.clocked > 0 {
+            write!(
+                f,
+                ", {} clocked over {} days",
+                format_minutes(self.clocked),
+                self.clocked_days
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Completion times and clocked totals of `title` in `hdps`
+pub fn habit_times(hdps: &[HabitDayPerformance], title: &str) -> HabitTimes {
+    let mut times = vec![];
+    let mut clocked = 0;
+    let mut clocked_days = 0;
+    for tp in hdps
+        .iter()
+        .flat_map(|hdp| hdp.todo_performance.iter())
+        .filter(|tp| tp.title() == title)
+    {
+        if let (Performance::Done, Some(time)) = (tp.performance(), tp.time()) {
+            times.push(time);
+        }
+        if tp.clocked_minutes() > 0 {
+            clocked += tp.clocked_minutes();
+            clocked_days += 1;
+        }
+    }
+    times.sort();
+    HabitTimes {
+        title: title.to_string(),
+        times,
+        clocked,
+        clocked_days,
+    }
+}
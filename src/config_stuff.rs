@@ -0,0 +1,175 @@
+//! `health-mode.toml`, the settings the notes themselves can't carry
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::alert_stuff::{AlertOutputs, Rule};
+use crate::data_stuff::{Aggregate, HabitProgress};
+use crate::goal_stuff::Goal;
+use crate::schedule_stuff::Schedule;
+use crate::theme_stuff::Theme;
+use crate::unit_stuff::Unit;
+
+pub const CONFIG_FILE_NAME: &str = "health-mode.toml";
+
+/// Everything the notes themselves can't tell us, read from `health-mode.toml`
+///
+/// ```toml
+/// [habits."Swim"]
+/// schedule = { kind = "per_week", times = 2 }
+///
+/// [habits."Sauna"]
+/// schedule = { kind = "weekdays", days = ["Sat", "Sun"] }
+///
+/// [habits."Parasym"]
+/// start = "2025-04-01"
+/// retired = "2025-08-31"
+///
+/// [objectives."Weight="]
+/// unit = "lb"
+/// aggregate = "min"
+/// goal = { target = 165.0, deadline = "2025-09-01", direction = "down" }
+///
+/// [[alerts]]
+/// when = { kind = "habit_missed", habit = "#1 Walk" }
+/// days = 4
+///
+/// [aliases]
+/// "Anki Ukrainian" = "Anki Ukrainian Lesson"
+///
+/// [theme]
+/// palette = "dark"
+/// ```
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub habits: BTreeMap<String, HabitConfig>,
+    pub objectives: BTreeMap<String, ObjectiveConfig>,
+    pub alerts: Vec<Rule>,
+    pub alert_outputs: AlertOutputs,
+    /// Old or misspelled title to the title it should count as
+    pub aliases: BTreeMap<String, String>,
+    pub theme: Theme,
+}
+
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HabitConfig {
+    pub schedule: Schedule,
+    /// First day the habit was tracked, the first day it shows up in the notes if left out
+    pub start: Option<NaiveDate>,
+    /// Last day the habit was tracked
+    pub retired: Option<NaiveDate>,
+}
+
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectiveConfig {
+    /// Target in the display unit when `unit` is set
+    pub goal: Option<Goal>,
+    /// Unit values are shown in, values written in another convertible unit are converted
+    pub unit: Option<Unit>,
+    /// How several readings on one day become the day's value
+    pub aggregate: Aggregate,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let raw = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let config: Config =
+            toml::from_str(&raw).map_err(|e| format!("{}: {e}", path.display()))?;
+        config
+            .validate()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Checks what parsing can't, that every alert rule names a habit or objective the notes
+    /// can have
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in &self.alerts {
+            rule.validate(self)?;
+        }
+        Ok(())
+    }
+
+    /// Loads `path` when it exists, otherwise falls back to an empty config
+    pub fn load_or_default(path: &Path) -> Result<Self, String> {
+        if path.exists() {
+            Config::load(path)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    pub fn schedule(&self, title: &str) -> Schedule {
+        self.habits
+            .get(title)
+            .map(|h| h.schedule.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn goal(&self, objective: &str) -> Option<&Goal> {
+        self.objectives.get(objective)?.goal.as_ref()
+    }
+
+    pub fn aggregate(&self, objective: &str) -> Aggregate {
+        self.objectives
+            .get(objective)
+            .map(|o| o.aggregate)
+            .unwrap_or_default()
+    }
+
+    pub fn unit(&self, objective: &str) -> Option<Unit> {
+        self.objectives.get(objective)?.unit.clone()
+    }
+
+    /// Habit titles the notes are expected to use, the built in ones plus the configured ones
+    pub fn known_habits(&self) -> Vec<&str> {
+        let mut known = HabitProgress::ordered_titles();
+        for title in self.habits.keys().chain(self.aliases.values()) {
+            if !known.contains(&title.as_str()) {
+                known.push(title);
+            }
+        }
+        known
+    }
+
+    /// Objective titles the notes are expected to use, the built in ones plus the configured ones
+    pub fn known_objectives(&self) -> Vec<&str> {
+        let mut known = HabitProgress::ordered_objectives();
+        for title in self.objectives.keys() {
+            if !known.contains(&title.as_str()) {
+                known.push(title);
+            }
+        }
+        known
+    }
+
+    /// The title `title` should be counted under. Whitespace is collapsed, aliases are
+    /// followed, and a known habit or objective title that only differs in case wins.
+    pub fn canonical_title(&self, title: &str) -> String {
+        let collapsed = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        let same = |a: &str| {
+            a.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+                == collapsed.to_lowercase()
+        };
+
+        let renamed = match self.aliases.iter().find(|(alias, _)| same(alias)) {
+            Some((_, target)) => target.as_str(),
+            None => collapsed.as_str(),
+        };
+        let known = self
+            .known_habits()
+            .into_iter()
+            .chain(self.known_objectives())
+            .find(|k| k.to_lowercase() == renamed.to_lowercase());
+        known.unwrap_or(renamed).to_string()
+    }
+}
@@ -1,23 +1,142 @@
-use crate::time_stuff::{days_in_month, CurrentDate};
+//! Day file parsing and the habit data model
+
+use crate::clock_stuff::{clock_minutes, closed_time};
+use crate::query_stuff::DayFilter;
+use crate::time_stuff::{month_name_to_num, month_num_to_name, DateSpan};
+use crate::unit_stuff::{parse_value, Quantity, Unit};
+use chrono::{Datelike, NaiveDate, NaiveTime};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// What to keep when an import and the org notes both have a value for the same day and title
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictRule {
+    /// The org notes always win
+    KeepOrg,
+    /// The imported value always wins
+    KeepImport,
+    /// A habit counts as done if either side says done, objectives keep the org value
+    AnyDone,
+}
+
+impl FromStr for ConflictRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-org" => Ok(ConflictRule::KeepOrg),
+            "keep-import" => Ok(ConflictRule::KeepImport),
+            "any-done" => Ok(ConflictRule::AnyDone),
+            _ => Err(format!(
+                "unknown conflict rule {s}, expected keep-org, keep-import or any-done"
+            )),
+        }
+    }
+}
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Performance {
     Done,
     Fail,
     Score(f32),
+    /// Objective value written with a unit
+    Measured(Quantity),
+}
+
+impl Performance {
+    /// Objective value as written in the notes, a bare number is a score
+    pub fn parse_objective(text: &str) -> Option<Performance> {
+        let performance = match parse_value(text)? {
+            (value, Some(unit)) => Performance::Measured(Quantity { value, unit }),
+            (value, None) => Performance::Score(value),
+        };
+        Some(performance)
+    }
+}
+
+/// How the readings of an objective on one day become that day's value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    #[default]
+    First,
+    Last,
+    Mean,
+    Min,
+    Max,
+    Sum,
+}
+
+impl Aggregate {
+    /// Day value of `values`, `None` when there are none
+    pub fn apply(self, values: &[f32]) -> Option<f32> {
+        let first = *values.first()?;
+        let value = match self {
+            Aggregate::First => first,
+            Aggregate::Last => *values.last()?,
+            Aggregate::Mean => values.iter().sum::<f32>() / values.len() as f32,
+            Aggregate::Min => values.iter().copied().fold(first, f32::min),
+            Aggregate::Max => values.iter().copied().fold(first, f32::max),
+            Aggregate::Sum => values.iter().sum(),
+        };
+        Some(value)
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Task {
     Todo(String),
     Objective(String),
 }
 
-#[derive(Clone)]
-pub struct TaskPerformance((Task, Performance));
+/// When a habit was done or a reading taken, from `CLOSED:`, `CLOCK:` and `@ HH:MM`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timing {
+    pub at: Option<NaiveTime>,
+    /// Minutes clocked on the habit that day
+    pub clocked: u32,
+}
+
+/// A habit mark or objective reading, with its timing when the notes have one
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TaskPerformance((Task, Performance), Timing);
 
 impl TaskPerformance {
+    pub fn new(task: Task, performance: Performance) -> Self {
+        TaskPerformance((task, performance), Timing::default())
+    }
+    pub fn at(mut self, time: NaiveTime) -> Self {
+        self.1.at = Some(time);
+        self
+    }
+    pub fn clocked(mut self, minutes: u32) -> Self {
+        self.1.clocked = minutes;
+        self
+    }
+    pub fn time(&self) -> Option<NaiveTime> {
+        self.1.at
+    }
+    pub fn clocked_minutes(&self) -> u32 {
+        self.1.clocked
+    }
+    pub fn title(&self) -> &str {
+        match &self.0 .0 {
+            Task::Todo(t) => t,
+            Task::Objective(t) => t,
+        }
+    }
+    /// Same task and performance under another title
+    pub fn renamed(&self, title: String) -> Self {
+        let task = match self.0 .0 {
+            Task::Todo(_) => Task::Todo(title),
+            Task::Objective(_) => Task::Objective(title),
+        };
+        TaskPerformance((task, self.0 .1.clone()), self.1.clone())
+    }
     pub fn task(&self) -> Task {
         self.0 .0.clone()
     }
@@ -26,15 +145,86 @@ impl TaskPerformance {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct HabitDayPerformance {
     pub year: String,
     pub month: String,
     pub day: String,
     pub todo_performance: Vec<TaskPerformance>,
     pub objective_performance: Vec<TaskPerformance>,
+    /// Org tags of the day, from `#+FILETAGS:` and headings other than habits and objectives
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Text of the day's `/NOTE/` headings
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+impl HabitDayPerformance {
+    pub fn new(date: NaiveDate) -> Self {
+        HabitDayPerformance {
+            year: date.year().to_string(),
+            month: month_num_to_name(date.month()).to_string(),
+            day: format!("{:02}", date.day()),
+            todo_performance: vec![],
+            objective_performance: vec![],
+            tags: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        let year: i32 = self.year.parse().unwrap();
+        let month = month_name_to_num(&self.month);
+        let day: u32 = self.day.parse().unwrap();
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// First recorded value of the objective `title` that day
+    pub fn objective(&self, title: &str) -> Option<f32> {
+        self.objective_by(title, Aggregate::First)
+    }
+
+    /// Every reading of the objective `title` that day, timed ones in time order after the
+    /// untimed ones
+    pub fn objective_values(&self, title: &str) -> Vec<f32> {
+        let mut readings: Vec<&TaskPerformance> = self
+            .objective_performance
+            .iter()
+            .filter(|o| o.title() == title)
+            .collect();
+        readings.sort_by_key(|o| o.time());
+        readings
+            .iter()
+            .filter_map(|o| match o.performance() {
+                Performance::Score(score) => Some(score),
+                Performance::Measured(quantity) => Some(quantity.value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The day's value of the objective `title`, its readings combined with `aggregate`
+    pub fn objective_by(&self, title: &str, aggregate: Aggregate) -> Option<f32> {
+        aggregate.apply(&self.objective_values(title))
+    }
+
+    /// Habits marked DONE and habits marked at all that day
+    pub fn done_count(&self) -> (u32, u32) {
+        let done = self
+            .todo_performance
+            .iter()
+            .filter(|t| matches!(t.performance(), Performance::Done))
+            .count() as u32;
+        (done, self.todo_performance.len() as u32)
+    }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct HabitProgress(pub Vec<HabitDayPerformance>);
 
 impl HabitProgress {
@@ -42,53 +232,36 @@ impl HabitProgress {
         &self.0
     }
 
-    pub fn filter_by_month(&self, month: String) -> Vec<HabitDayPerformance> {
+    pub fn filter_by_span(&self, span: &DateSpan) -> Vec<HabitDayPerformance> {
         let hdps = &self.0;
-        let monthly_hdps = hdps
+        let span_hdps = hdps
             .iter()
-            .filter(|x| x.month == month)
-            .map(|x| x.clone())
+            .filter(|x| span.contains(x.date()))
+            .cloned()
             .collect::<Vec<HabitDayPerformance>>();
-        monthly_hdps
+        span_hdps
     }
 
-    pub fn oldest_month_hdp(&self, month: String) -> HabitDayPerformance {
-        let hdps = &self.0;
-        let month_hdps: Vec<&HabitDayPerformance> =
-            hdps.iter().filter(|x| x.month == month).collect();
-        if let Some(youngest_hdps) = month_hdps
-            .iter()
-            .min_by_key(|x| x.day.parse::<i32>().unwrap())
-        {
-            return youngest_hdps.to_owned().to_owned();
-        } else {
-            panic!("youngest not found");
-        }
+    /// Days carrying the tag `tag`
+    pub fn filter_by_tag(&self, tag: &str) -> Vec<HabitDayPerformance> {
+        self.0.iter().filter(|x| x.has_tag(tag)).cloned().collect()
     }
 
-    pub fn doc_titles(&self) -> HashSet<(String, String)> {
-        let mut year_months: HashSet<(String, String)> = HashSet::new();
-        let hdps = &self.0;
-        for hdp in hdps.iter() {
-            let year = hdp.year.clone();
-            let month = hdp.month.clone();
-            year_months.insert((year, month));
-        }
-        year_months
+    /// Days `filter` lets through
+    pub fn query(&self, filter: &DayFilter) -> Vec<HabitDayPerformance> {
+        self.0
+            .iter()
+            .filter(|x| filter.matches(x))
+            .cloned()
+            .collect()
     }
 
-    pub fn get_current_hdp(&self) -> HabitDayPerformance {
-        let hdps = &self.0;
-        let len = hdps.len();
-        let current = &hdps[len - 1];
-        let clone = current.clone();
-        clone
-    }
-    pub fn get_oldest_hdp(&self) -> HabitDayPerformance {
+    /// First to last day with data, if there is any
+    pub fn date_span(&self) -> Option<DateSpan> {
         let hdps = &self.0;
-        let oldest = &hdps[0];
-        let clone = oldest.clone();
-        clone
+        let first = hdps.first()?.date();
+        let last = hdps.last()?.date();
+        Some(DateSpan::new(first, last))
     }
 
     pub fn ordered_titles<'a>() -> Vec<&'a str> {
@@ -115,6 +288,96 @@ impl HabitProgress {
         ];
         t
     }
+    /// Habit names from `ordered_titles` with the `#N` instances folded together, in first seen order
+    pub fn ordered_habit_names<'a>() -> Vec<&'a str> {
+        let mut names: Vec<&str> = vec![];
+        for title in HabitProgress::ordered_titles() {
+            let (name, _) = habit_instance(title);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    pub fn ordered_objectives<'a>() -> Vec<&'a str> {
+        vec!["Weight=", "GERD-Symptoms(0-10)=", "Mood(0-10)="]
+    }
+
+    /// Folds days from another source into this one, day by day and title by title
+    pub fn merge(&mut self, other: HabitProgress, rule: ConflictRule) {
+        for incoming in other.0 {
+            let date = incoming.date();
+            match self.0.iter_mut().find(|hdp| hdp.date() == date) {
+                Some(existing) => {
+                    merge_tasks(
+                        &mut existing.todo_performance,
+                        incoming.todo_performance,
+                        rule,
+                    );
+                    merge_readings(
+                        &mut existing.objective_performance,
+                        incoming.objective_performance,
+                        rule,
+                    );
+                }
+                None => self.0.push(incoming),
+            }
+        }
+        self.0.sort_by_key(|hdp| hdp.date());
+    }
+
+    /// Moves every habit and objective to the title `canonical` gives for it. Habits that end
+    /// up twice in a day are folded into one, counting as done if either was, objective
+    /// readings are all kept.
+    pub fn rename_titles(&mut self, canonical: impl Fn(&str) -> String) {
+        for hdp in &mut self.0 {
+            let renamed = hdp
+                .todo_performance
+                .iter()
+                .map(|tp| tp.renamed(canonical(tp.title())));
+            let mut folded = vec![];
+            for tp in renamed {
+                merge_tasks(&mut folded, vec![tp], ConflictRule::AnyDone);
+            }
+            hdp.todo_performance = folded;
+            for tp in &mut hdp.objective_performance {
+                *tp = tp.renamed(canonical(tp.title()));
+            }
+        }
+    }
+
+    /// Puts every objective value into the unit `unit_for` gives for its title. Values written
+    /// without a unit are taken to be in the unit `bare_unit` gives, and values whose unit can't
+    /// be converted are left as written.
+    pub fn convert_objectives(
+        &mut self,
+        unit_for: impl Fn(&str) -> Option<Unit>,
+        bare_unit: impl Fn(&str) -> Option<Unit>,
+    ) {
+        for hdp in &mut self.0 {
+            for tp in &mut hdp.objective_performance {
+                let Some(unit) = unit_for(tp.title()) else {
+                    continue;
+                };
+                let quantity = match tp.performance() {
+                    Performance::Measured(quantity) => quantity,
+                    Performance::Score(value) => match bare_unit(tp.title()) {
+                        Some(unit) => Quantity { value, unit },
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                if let Some(converted) = quantity.convert(&unit) {
+                    *tp = TaskPerformance(
+                        (tp.task(), Performance::Measured(converted)),
+                        tp.1.clone(),
+                    );
+                }
+            }
+        }
+    }
+
     pub fn all_unique_todo_titles(&self) -> Vec<String> {
         let all_hdp = &self.0;
 
@@ -162,6 +425,7 @@ impl fmt::Display for Performance {
             Performance::Done => write!(f, "Done"),
             Performance::Fail => write!(f, "Failed"),
             Performance::Score(score) => write!(f, "Score: {:.1}", score),
+            Performance::Measured(quantity) => write!(f, "Measured: {quantity}"),
         }
     }
 }
@@ -188,6 +452,13 @@ impl fmt::Display for HabitDayPerformance {
             writeln!(f, "  {}. {}", i + 1, obj)?;
         }
 
+        if !self.tags.is_empty() {
+            writeln!(f, "Tags: {}", self.tags.join(", "))?;
+        }
+        for note in &self.notes {
+            writeln!(f, "Note: {note}")?;
+        }
+
         Ok(())
     }
 }
@@ -208,48 +479,135 @@ impl fmt::Display for HabitProgress {
     }
 }
 
+/// Splits a numbered title like "#2 Walk" into the habit name and its instance number.
+/// Titles without a `#N` prefix are a single instance habit.
+pub fn habit_instance(title: &str) -> (&str, Option<u32>) {
+    if let Some(rest) = title.strip_prefix('#') {
+        if let Some((number, name)) = rest.split_once(' ') {
+            if let Ok(instance) = number.parse::<u32>() {
+                return (name.trim(), Some(instance));
+            }
+        }
+    }
+    (title, None)
+}
+
+fn merge_tasks(
+    existing: &mut Vec<TaskPerformance>,
+    incoming: Vec<TaskPerformance>,
+    rule: ConflictRule,
+) {
+    for tp in incoming {
+        match existing.iter_mut().find(|e| e.title() == tp.title()) {
+            Some(current) => {
+                let replace = match (rule, current.performance(), tp.performance()) {
+                    (ConflictRule::KeepOrg, _, _) => false,
+                    (ConflictRule::KeepImport, _, _) => true,
+                    (ConflictRule::AnyDone, Performance::Fail, Performance::Done) => true,
+                    (ConflictRule::AnyDone, _, _) => false,
+                };
+                if replace {
+                    *current = tp;
+                }
+            }
+            None => existing.push(tp),
+        }
+    }
+}
+
+/// Objectives keep every reading of a day. A title the other side also has is either left
+/// alone or has all its readings replaced.
+fn merge_readings(
+    existing: &mut Vec<TaskPerformance>,
+    incoming: Vec<TaskPerformance>,
+    rule: ConflictRule,
+) {
+    let existing_titles: HashSet<String> =
+        existing.iter().map(|tp| tp.title().to_string()).collect();
+    let incoming_titles: HashSet<String> =
+        incoming.iter().map(|tp| tp.title().to_string()).collect();
+    if rule == ConflictRule::KeepImport {
+        existing.retain(|tp| !incoming_titles.contains(tp.title()));
+        existing.extend(incoming);
+    } else {
+        existing.extend(
+            incoming
+                .into_iter()
+                .filter(|tp| !existing_titles.contains(tp.title())),
+        );
+    }
+}
+
+/// Habit heading, its trailing `:tag:` groups are left out of the title
+static HABIT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\*.*(DONE|FAIL) /HABIT/ (.*?)(?:\s+:[^\s:]+(?::[^\s:]+)*:)?\s*$").unwrap()
+});
+static OBJECTIVE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"/OBJECTIVE/ (.*)").unwrap());
+static READING_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*- (.*)").unwrap());
+static HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\*+\s+.*?)(?:\s+(:[^\s:]+(?::[^\s:]+)*:))?\s*$").unwrap());
+static NOTE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"/NOTE/\s*(.*)").unwrap());
+
+/// Value and `@ HH:MM` time of a reading line like `82.4 kg @ 07:30`
+fn parse_reading(text: &str) -> Option<(Performance, Option<NaiveTime>)> {
+    let performance = Performance::parse_objective(text)?;
+    let time = text
+        .split_once('@')
+        .and_then(|(_, at)| NaiveTime::parse_from_str(at.trim(), "%H:%M").ok());
+    Some((performance, time))
+}
+
 pub fn process_org_file(content: String) -> Vec<Vec<TaskPerformance>> {
     // Parse the Org file
     // println!("{}", content);
+    let lines: Vec<&str> = content.lines().collect();
+    // Lines under the heading at `index`, up to the next heading
+    let body = |index: usize| {
+        lines[index + 1..]
+            .iter()
+            .take_while(|l| !l.starts_with('*'))
+    };
+
     let tp = {
         let mut todo_performance: Vec<TaskPerformance> = Vec::new();
-        let re = Regex::new(r"\*.*(DONE|FAIL) /HABIT/ (.*)").unwrap();
-        for cap in re.captures_iter(&content) {
+        for (index, line) in lines.iter().enumerate() {
+            let Some(cap) = HABIT_RE.captures(line) else {
+                continue;
+            };
             let (_, [perf, title]) = cap.extract();
-            // println!("{:?}", perf);
-            // println!("{:?}", title);
             let t = Task::Todo(title.to_string());
             let p = match perf {
                 "FAIL" => Performance::Fail,
                 "DONE" => Performance::Done,
                 _ => Performance::Fail,
             };
-            todo_performance.push(TaskPerformance((t, p)));
+            let mut habit = TaskPerformance::new(t, p);
+            if let Some(time) = body(index).find_map(|l| closed_time(l)) {
+                habit = habit.at(time);
+            }
+            let clocked = body(index).filter_map(|l| clock_minutes(l)).sum();
+            todo_performance.push(habit.clocked(clocked));
         }
         todo_performance
     };
 
     let op = {
         let mut objective_performance: Vec<TaskPerformance> = Vec::new();
-        let ot_re = Regex::new(r"/OBJECTIVE/ (.*)").unwrap();
-        for cap in ot_re.captures_iter(&content) {
+        for (index, line) in lines.iter().enumerate() {
+            let Some(cap) = OBJECTIVE_RE.captures(line) else {
+                continue;
+            };
             let (_, [objective_title]) = cap.extract();
-            // println!("{:?}", objective_title);
-            let otrp = objective_title.replace("(", r"\(");
-            let final_otrp = otrp.replace(")", r"\)");
-            let prefix = r"/OBJECTIVE/ ";
-            let suffix = r".*\n{1,3}.*- (\d{1,3}\.\d{1}|\d{1,3})";
-            let ov_re_str = format!("{}{}{}", prefix, final_otrp, suffix);
-            // println!("{:?}", ov_re_str);
-            let rds = ov_re_str.replace(r"\\", r"\");
-            let ov_re = Regex::new(&rds).unwrap();
-            for v_cap in ov_re.captures_iter(&content) {
-                let (_, [objective_value]) = v_cap.extract();
-                let ov: f32 = objective_value.to_string().parse().unwrap();
-                let e = TaskPerformance((
-                    Task::Objective(objective_title.to_string()),
-                    Performance::Score(ov),
-                ));
+            // Every `- <value>` line up to the next heading is a reading
+            let readings = body(index)
+                .filter_map(|l| READING_RE.captures(l))
+                .filter_map(|v_cap| parse_reading(v_cap.get(1)?.as_str()));
+            for (performance, time) in readings {
+                let mut e =
+                    TaskPerformance::new(Task::Objective(objective_title.to_string()), performance);
+                if let Some(time) = time {
+                    e = e.at(time);
+                }
                 objective_performance.push(e);
             }
         }
@@ -259,86 +617,78 @@ pub fn process_org_file(content: String) -> Vec<Vec<TaskPerformance>> {
     vec![tp, op]
 }
 
-pub fn collect_org_data() -> HabitProgress {
-    let months = [
-        "January",
-        "Feburary",
-        "March",
-        "April",
-        "May",
-        "June",
-        "July",
-        "August",
-        "September",
-        "October",
-        "November",
-        "December",
-    ];
-    let start_date = 22;
-    let start_month = 2;
-    let start_year = 2025;
-    let cd = CurrentDate::new();
-    let current_year: usize = cd.year.parse().unwrap();
-    let current_month = cd.month;
-    let current_day: usize = cd.day.parse().unwrap();
-    let year_len = (current_year - start_year + 1);
-
-    let mut habit_progress: Vec<HabitDayPerformance> = vec![];
-
-    for year in 0..year_len {
-        for month in months {
-            let mut last_year = false;
-            if year_len - 1 == year {
-                last_year = true;
+/// Tags and notes of a day file. Tags come from `#+FILETAGS: :travel:sick:` and the
+/// `:tag:` endings of headings other than habits and objectives, notes are `/NOTE/` headings
+/// with the text under them.
+pub fn process_day_notes(content: &str) -> (Vec<String>, Vec<String>) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut tags: Vec<String> = vec![];
+    let mut notes = vec![];
+
+    let mut add_tags = |written: &str| {
+        for tag in written.split(':').filter(|t| !t.is_empty()) {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
             }
+        }
+    };
 
-            let days = days_in_month(months[start_month]);
-
-            for day_index in 0..days.len() {
-                // println!("{day_index}");
-
-                let mut day_string = "01".to_string();
-
-                let day = days[day_index];
-
-                if day < 10 {
-                    day_string = format!("0{day}");
-                } else {
-                    day_string = format!("{day}");
-                }
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(filetags) = line.strip_prefix("#+FILETAGS:") {
+            add_tags(filetags.trim());
+            continue;
+        }
+        let Some(cap) = HEADING_RE.captures(line) else {
+            continue;
+        };
+        if line.contains("/HABIT/") || line.contains("/OBJECTIVE/") {
+            continue;
+        }
+        if let Some(heading_tags) = cap.get(2) {
+            add_tags(heading_tags.as_str());
+        }
+        let Some(note) = NOTE_RE.captures(&cap[1]) else {
+            continue;
+        };
+        let mut text = vec![note[1].trim()];
+        text.extend(
+            lines[index + 1..]
+                .iter()
+                .take_while(|l| !l.starts_with('*'))
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty()),
+        );
+        notes.push(text.join(" ").trim().to_string());
+    }
+    (tags, notes)
+}
 
-                let string_year_val = start_year + year;
-                let file_path = format!(
-                    "/home/test/code/notes/habits/{string_year_val}/{month}/{day_string}.org"
-                );
-                // println!("{file_path}");
-
-                if let Ok(file_contents) = std::fs::read_to_string(file_path) {
-                    let mut p_vec = process_org_file(file_contents);
-                    let todos = mem::take(&mut p_vec[0]);
-                    let objectives = mem::take(&mut p_vec[1]);
-                    let hdp_year = string_year_val.to_string();
-                    let hdp_month = month.to_string();
-                    let hdp_day = day_string;
-
-                    let hdp = HabitDayPerformance {
-                        todo_performance: todos,
-                        objective_performance: objectives,
-                        year: hdp_year,
-                        month: hdp_month,
-                        day: hdp_day,
-                    };
-                    habit_progress.push(hdp);
-                    // println!("file exists");
-                } else {
-                    // println!("file does not exist");
-                }
-            }
+pub fn collect_org_data(root: &Path, start: NaiveDate, end: NaiveDate) -> HabitProgress {
+    let mut habit_progress: Vec<HabitDayPerformance> = vec![];
 
-            if month == current_month && last_year {
-                break;
-            }
+    for date in start.iter_days().take_while(|d| *d <= end) {
+        let file_path = day_file_path(root, date);
+
+        if let Ok(file_contents) = std::fs::read_to_string(file_path) {
+            let (tags, notes) = process_day_notes(&file_contents);
+            let mut p_vec = process_org_file(file_contents);
+            let todos = mem::take(&mut p_vec[0]);
+            let objectives = mem::take(&mut p_vec[1]);
+
+            let mut hdp = HabitDayPerformance::new(date);
+            hdp.todo_performance = todos;
+            hdp.objective_performance = objectives;
+            hdp.tags = tags;
+            hdp.notes = notes;
+            habit_progress.push(hdp);
         }
     }
     HabitProgress(habit_progress)
 }
+
+/// Path of the org file holding a single day, `{root}/{year}/{Month}/{DD}.org`
+pub fn day_file_path(root: &Path, date: NaiveDate) -> PathBuf {
+    root.join(date.year().to_string())
+        .join(month_num_to_name(date.month()))
+        .join(format!("{:02}.org", date.day()))
+}
@@ -1,46 +1,128 @@
-use full_palette::GREEN_200;
-use full_palette::GREEN_300;
-use full_palette::GREEN_500;
-use full_palette::GREY;
-use full_palette::GREY_200;
-use full_palette::GREY_300;
-use full_palette::GREY_500;
-use full_palette::GREY_700;
-use full_palette::GREY_900;
 use plotters::coord::ranged1d::SegmentedCoord;
-use plotters::coord::types::Monthly;
 use plotters::coord::types::RangedCoordi32;
-use plotters::coord::types::Yearly;
 use plotters::coord::Shift;
 use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
 
-use crate::analyze_stuff::TimeFrame;
 use crate::analyze_stuff::TodoTimeFrameSettings;
 use crate::data_stuff::HabitDayPerformance;
-use crate::data_stuff::HabitProgress;
-use crate::data_stuff::Performance;
-use crate::data_stuff::Task;
+use crate::schedule_stuff::HabitStatus;
+use crate::schedule_stuff::StatusGrid;
+use crate::theme_stuff::Theme;
+use crate::time_stuff::TimeFrame;
 
-type HeatmapSettings<'a> = ChartContext<
+type HeatmapSettings<'a, DB> = ChartContext<
     'a,
-    BitMapBackend<'a>,
+    DB,
     Cartesian2d<SegmentedCoord<RangedCoordi32>, SegmentedCoord<RangedCoordi32>>,
 >;
 
-pub fn build_chart<'a, 'b>(
-    root: &'a DrawingArea<BitMapBackend<'b>, Shift>,
+/// Pixel size of the column title font
+const LABEL_FONT: u32 = 15;
+/// Rough pixel width of one character of a column title
+const CHAR_WIDTH: u32 = 7;
+/// Pixel height of one line of a wrapped column title
+const LINE_HEIGHT: u32 = 17;
+/// Pixels around the chart, above the caption and beside the day labels
+const MARGIN: u32 = 20;
+const CAPTION_HEIGHT: u32 = 40;
+const DAY_LABEL_WIDTH: u32 = 60;
+/// Pixel height of the legend strip under the heatmap
+pub const LEGEND_HEIGHT: u32 = 40;
+
+/// `title` broken at spaces into lines of at most `width` characters, words longer than a
+/// line are split with their last piece on a line of its own
+pub fn wrap(title: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    // The last piece of a split word keeps its line to itself
+    let mut split = false;
+    for word in title.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if !line.is_empty() && !split && line.chars().count() + 1 + word.len() <= width {
+            line.push(' ');
+            line.extend(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        split = word.len() > width;
+        while word.len() > width {
+            lines.push(word.drain(..width).collect());
+        }
+        line = word.into_iter().collect();
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Lines of each column title as the theme writes them in columns `column_width` pixels wide
+fn column_labels(titles: &[&str], theme: &Theme, column_width: u32) -> Vec<Vec<String>> {
+    titles
+        .iter()
+        .map(|title| {
+            let label = theme.label(title);
+            match !theme.rotate_labels && theme.wrap_labels {
+                true => wrap(&label, (column_width / CHAR_WIDTH) as usize),
+                false => vec![label],
+            }
+        })
+        .collect()
+}
+
+/// Pixel height the column titles take above the cells
+fn label_area_height(titles: &[&str], theme: &Theme, column_width: u32) -> u32 {
+    let labels = column_labels(titles, theme, column_width);
+    match theme.rotate_labels {
+        true => {
+            let longest = labels.iter().flatten().map(|l| l.chars().count());
+            longest.max().unwrap_or_default() as u32 * CHAR_WIDTH + 20
+        }
+        false => labels.iter().map(Vec::len).max().unwrap_or(1) as u32 * LINE_HEIGHT + 10,
+    }
+}
+
+/// Pixel size of a heatmap with a column per title and `rows` days, the theme's
+/// `heatmap_size` when it sets one and otherwise room for a `cell_size` cell each
+pub fn canvas_size(titles: &[&str], rows: u32, theme: &Theme) -> (u32, u32) {
+    if let Some(size) = theme.heatmap_size {
+        return size;
+    }
+    let (cell_width, cell_height) = theme.cell_size;
+    let width = 2 * MARGIN + DAY_LABEL_WIDTH + titles.len() as u32 * cell_width;
+    let height = 2 * MARGIN
+        + CAPTION_HEIGHT
+        + label_area_height(titles, theme, cell_width)
+        + rows * cell_height
+        + LEGEND_HEIGHT;
+    (width, height)
+}
+
+pub fn build_chart<'b, DB: DrawingBackend>(
+    root: &'b DrawingArea<DB, Shift>,
     settings: &TimeFrame<TodoTimeFrameSettings<'b>>,
-) -> Result<HeatmapSettings<'b>, String> {
-    let s: &TodoTimeFrameSettings = match settings {
-        TimeFrame::Year(y) => y,
-        TimeFrame::Month(m) => m,
-    };
+) -> Result<HeatmapSettings<'b, DB>, String> {
+    let s: &TodoTimeFrameSettings = settings.inner();
+    if s.y_axis_size == 0 {
+        return Err(format!("{} has no days to chart", s.doc_title));
+    }
+    let t = &s.theme;
+    let columns = s.column_titles.len().max(1) as u32;
+    let width = root.dim_in_pixel().0;
+    let column_width = width.saturating_sub(2 * MARGIN + DAY_LABEL_WIDTH) / columns;
 
     let chart = ChartBuilder::on(root)
-        .set_label_area_size(LabelAreaPosition::Top, 30)
-        .set_label_area_size(LabelAreaPosition::Left, 30)
-        .caption(&s.doc_title, ("sans-serif", 20))
-        .margin(20)
+        .set_label_area_size(
+            LabelAreaPosition::Top,
+            label_area_height(&s.column_titles, t, column_width),
+        )
+        .set_label_area_size(LabelAreaPosition::Left, DAY_LABEL_WIDTH)
+        .caption(&s.doc_title, t.text(20))
+        .margin(MARGIN)
         .build_cartesian_2d(
             (0..(s.x_axis_size as i32)).into_segmented(),
             (0..((s.y_axis_size - 1) as i32)).into_segmented(),
@@ -49,19 +131,12 @@ pub fn build_chart<'a, 'b>(
     Ok(chart)
 }
 
-fn april_days_inverted() -> Vec<i32> {
-    // April has 30 days, in reverse order
-    (1..=30).rev().collect()
-}
-
-pub fn style_chart<'a>(
-    mut chart: HeatmapSettings<'a>,
+pub fn style_chart<'a, DB: DrawingBackend>(
+    mut chart: HeatmapSettings<'a, DB>,
     settings: &TimeFrame<TodoTimeFrameSettings<'a>>,
-) -> Result<HeatmapSettings<'a>, String> {
-    let s: &TodoTimeFrameSettings = match settings {
-        TimeFrame::Year(y) => y,
-        TimeFrame::Month(m) => m,
-    };
+) -> Result<HeatmapSettings<'a, DB>, String> {
+    let s: &TodoTimeFrameSettings = settings.inner();
+    let t = &s.theme;
 
     chart
         .configure_mesh()
@@ -74,16 +149,15 @@ pub fn style_chart<'a>(
                 SegmentValue::Exact(idx) => *idx,
                 _ => return "".to_string(),
             };
-            let title = s.column_titles[idx as usize];
-            let mut title_string = title.to_string();
-            let title_len = title_string.len();
-            if title_len > 6 {
-                title_string.truncate(6);
-                title_string.push_str("...");
+            // Rotated and wrapped titles are drawn by draw_column_labels
+            match t.rotate_labels || t.wrap_labels {
+                true => "".to_string(),
+                false => t.label(s.column_titles[idx as usize]),
             }
-            title_string
         })
-        .x_label_style(("sans-serif", 15).into_font().color(&BLACK))
+        .x_label_style(t.text(LABEL_FONT))
+        .y_label_style(t.text(12))
+        .axis_style(t.colours().foreground)
         .y_labels(s.y_axis_size as usize)
         .y_label_formatter(&|x| {
             let idx: i32 = match x {
@@ -91,66 +165,142 @@ pub fn style_chart<'a>(
                 SegmentValue::Exact(idx) => *idx,
                 _ => return "".to_string(),
             };
-            let inverse_label = ((idx) - s.y_axis_size as i32).abs();
-            inverse_label.to_string()
+            // Rows run top to bottom, first day at the top
+            let day_number = ((idx) - s.y_axis_size as i32).unsigned_abs();
+            settings.day_label(s.span.nth_day(day_number))
         })
         .draw()
         .unwrap();
     Ok(chart)
 }
 
-pub fn draw_rect<'a>(
-    chart: HeatmapSettings<'a>,
-    hdps: &Vec<HabitDayPerformance>,
-    settings: &TimeFrame<TodoTimeFrameSettings<'a>>,
-) -> HeatmapSettings<'a> {
-    let s: &TodoTimeFrameSettings = match settings {
-        TimeFrame::Year(y) => y,
-        TimeFrame::Month(m) => m,
+/// Column titles above the columns, written bottom to top or broken over lines as the theme
+/// says
+pub fn draw_column_labels<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    chart: &HeatmapSettings<DB>,
+    settings: &TimeFrame<TodoTimeFrameSettings>,
+) {
+    let s: &TodoTimeFrameSettings = settings.inner();
+    let t = &s.theme;
+    if !t.rotate_labels && !t.wrap_labels {
+        return;
+    }
+    let top = s.y_axis_size as i32;
+    let (x0, y0) = root.get_base_pixel();
+    let cell = |idx: usize| {
+        let (x, y) =
+            chart.backend_coord(&(SegmentValue::CenterOf(idx as i32), SegmentValue::Exact(top)));
+        (x - x0, y - y0 - 10)
+    };
+    let (left, _) = cell(0);
+    let column_width = match s.column_titles.len() {
+        0 | 1 => root.dim_in_pixel().0,
+        n => ((cell(n - 1).0 - left) / (n as i32 - 1)) as u32,
     };
 
-    let mut style = ShapeStyle {
-        color: RED.into(),
-        filled: true,
-        stroke_width: 2,
+    let style = match t.rotate_labels {
+        true => t
+            .text(LABEL_FONT)
+            .transform(FontTransform::Rotate270)
+            .pos(Pos::new(HPos::Left, VPos::Center)),
+        false => t.text(LABEL_FONT).pos(Pos::new(HPos::Center, VPos::Bottom)),
     };
+    let labels = column_labels(&s.column_titles, t, column_width);
+    for (idx, lines) in labels.iter().enumerate() {
+        let (x, y) = cell(idx);
+        // Last line just above the cells
+        for (n, line) in lines.iter().rev().enumerate() {
+            root.draw_text(line, &style, (x, y - (n as u32 * LINE_HEIGHT) as i32))
+                .unwrap();
+        }
+    }
+}
+
+/// Key to the cell colours, in a strip `area` under the heatmap
+pub fn draw_legend<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    theme: &Theme,
+    grouped: bool,
+) {
+    let p = theme.colours();
+    // Label, fill and whether the cell is hatched
+    let mut entries = vec![
+        ("Done", Some(p.done), false),
+        ("Missed", Some(p.missed), false),
+    ];
+    if grouped {
+        entries.push(("Partly done", Some(blend(p.missed, p.done, 0.5)), false));
+    }
+    entries.push(("Not scheduled", Some(p.not_scheduled), false));
+    entries.push(("Not tracked", Some(p.inactive), true));
+    entries.push(("No entry", None, false));
+
+    let style = theme
+        .text(LABEL_FONT)
+        .pos(Pos::new(HPos::Left, VPos::Center));
+    let size = 16;
+    let y = (LEGEND_HEIGHT / 2) as i32;
+    let mut x = (MARGIN + DAY_LABEL_WIDTH) as i32;
+    for (label, colour, hatched) in entries {
+        let corners = [(x, y - size / 2), (x + size, y + size / 2)];
+        if let Some(colour) = colour {
+            area.draw(&Rectangle::new(corners, colour.filled()))
+                .unwrap();
+        }
+        if hatched {
+            area.draw(&PathElement::new(corners, p.inactive_hatch))
+                .unwrap();
+        }
+        area.draw(&Rectangle::new(corners, p.foreground.stroke_width(1)))
+            .unwrap();
+        area.draw_text(label, &style, (x + size + 6, y)).unwrap();
+        x += size + 6 + label.chars().count() as i32 * CHAR_WIDTH as i32 + 24;
+    }
+}
+
+/// Colour `t` of the way from `from` to `to`
+pub(crate) fn blend(from: RGBColor, to: RGBColor, t: f64) -> RGBColor {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    RGBColor(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
+
+pub fn draw_rect<'a, DB: DrawingBackend>(
+    chart: HeatmapSettings<'a, DB>,
+    hdps: &[HabitDayPerformance],
+    statuses: &StatusGrid,
+    settings: &TimeFrame<TodoTimeFrameSettings<'a>>,
+) -> HeatmapSettings<'a, DB> {
+    let s: &TodoTimeFrameSettings = settings.inner();
+    let p = s.theme.colours();
 
-    let y_offset = s.y_offset;
     let y_axis_size = s.y_axis_size;
     let titles = &s.column_titles;
 
-    for (index, hdp) in hdps.iter().enumerate() {
-        let doy = index + y_offset as usize;
-        for todo in &hdp.todo_performance {
-            match todo.performance() {
-                Performance::Fail => {
-                    style = ShapeStyle {
-                        color: GREY_700.into(),
-                        filled: true,
-                        stroke_width: 2,
-                    };
-                }
-                Performance::Done => {
-                    style = ShapeStyle {
-                        color: GREEN_200.into(),
-                        filled: true,
-                        stroke_width: 2,
-                    };
+    for hdp in hdps {
+        let date = hdp.date();
+        let doy = s.span.day_number(date) as usize;
+        for (title_index, title) in titles.iter().enumerate() {
+            let status = statuses.get(*title).and_then(|days| days.get(&date));
+            let color = match status {
+                Some(HabitStatus::Done) => p.done,
+                Some(HabitStatus::Missed) => p.missed,
+                Some(HabitStatus::NotScheduled) => p.not_scheduled,
+                Some(HabitStatus::Partial(done, expected)) => {
+                    blend(p.missed, p.done, *done as f64 / *expected as f64)
                 }
-                _ => {}
+                Some(HabitStatus::Inactive) => p.inactive,
+                Some(HabitStatus::Unrecorded) | None => continue,
             };
-            let td_title = match todo.task() {
-                Task::Todo(s) => s,
-                _ => panic!("expected todo"),
+            let style = ShapeStyle {
+                color: color.to_rgba(),
+                filled: true,
+                stroke_width: 2,
             };
-            let title_index = titles.iter().position(|e| e == &td_title).unwrap();
-            // println!("y_offset {}", y_offset);
-            // println!("day of year {}", doy);
-            // println!("todo title {}, title index {}", td_title, title_index);
             let left_lower_x = title_index;
-            let left_lower_y = ((doy as i32 - 1) - y_axis_size as i32).abs() as usize;
+            let left_lower_y = ((doy as i32 - 1) - y_axis_size as i32).unsigned_abs() as usize;
             let right_upper_x = title_index + 1;
-            let right_upper_y = (doy as i32 - y_axis_size as i32).abs() as usize;
+            let right_upper_y = (doy as i32 - y_axis_size as i32).unsigned_abs() as usize;
             // println!(
             //    "llx {}, lly {}, rux {}, ruy {}\n\n",
             //   left_lower_x, left_lower_y, right_upper_x, right_upper_y
@@ -174,26 +324,44 @@ pub fn draw_rect<'a>(
                 style,
             );
             chart.plotting_area().draw(&rect).unwrap();
+
+            // Hatch days the habit wasn't being tracked so they don't read as missed
+            if status == Some(&HabitStatus::Inactive) {
+                let hatch = PathElement::new(
+                    [
+                        (
+                            SegmentValue::Exact(left_lower_x as i32),
+                            SegmentValue::Exact(left_lower_y as i32),
+                        ),
+                        (
+                            SegmentValue::Exact(right_upper_x as i32),
+                            SegmentValue::Exact(right_upper_y as i32),
+                        ),
+                    ],
+                    p.inactive_hatch,
+                );
+                chart.plotting_area().draw(&hatch).unwrap();
+            }
         }
     }
 
     chart
 }
 
-pub fn draw_grid_lines<'a>(
-    chart: HeatmapSettings<'a>,
+pub fn draw_grid_lines<'a, DB: DrawingBackend>(
+    chart: HeatmapSettings<'a, DB>,
     settings: &TimeFrame<TodoTimeFrameSettings<'a>>,
-) -> HeatmapSettings<'a> {
-    let s: &TodoTimeFrameSettings = match settings {
-        TimeFrame::Year(y) => y,
-        TimeFrame::Month(m) => m,
-    };
+) -> HeatmapSettings<'a, DB> {
+    let s: &TodoTimeFrameSettings = settings.inner();
     let x_axis_size = s.x_axis_size as i32;
     let y_axis_size = s.y_axis_size as i32;
+    if !s.theme.cell_borders {
+        return chart;
+    }
 
     let drawing_area = chart.plotting_area();
     let black_thin = ShapeStyle {
-        color: BLACK.into(),
+        color: s.theme.colours().foreground.to_rgba(),
         filled: false,
         stroke_width: 1,
     };
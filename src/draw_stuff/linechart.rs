@@ -1,128 +1,377 @@
-use full_palette::GREEN_200;
-use full_palette::GREEN_300;
-use full_palette::GREEN_500;
-use full_palette::GREY;
-use full_palette::GREY_200;
-use full_palette::GREY_300;
-use full_palette::GREY_500;
-use full_palette::GREY_700;
-use full_palette::GREY_900;
-use plotters::coord::ranged1d::SegmentedCoord;
-use plotters::coord::types::Monthly;
-use plotters::coord::types::Yearly;
-use plotters::coord::types::{RangedCoordf32, RangedCoordi32};
+use chrono::{Days, NaiveDate};
+use plotters::coord::combinators::WithKeyPoints;
+use plotters::coord::types::{RangedCoordf32, RangedDate};
 use plotters::coord::Shift;
 use plotters::prelude::*;
 
 use crate::analyze_stuff::ObjTimeFrameSettings;
-use crate::analyze_stuff::TimeFrame;
 use crate::data_stuff::HabitDayPerformance;
-use crate::data_stuff::Performance;
-use crate::data_stuff::Task;
+use crate::theme_stuff::Theme;
+use crate::time_stuff::{DateSpan, TickStep, TimeFrame};
 
-type LineChartSettings<'a> =
-    ChartContext<'a, BitMapBackend<'a>, Cartesian2d<RangedCoordf32, RangedCoordf32>>;
+type DateAxis = WithKeyPoints<RangedDate<NaiveDate>>;
+type LineChartSettings<'a, DB> = ChartContext<'a, DB, Cartesian2d<DateAxis, RangedCoordf32>>;
 
-pub fn build_chart<'a, 'b>(
-    root: &'a DrawingArea<BitMapBackend<'b>, Shift>,
+/// Dates of `span` with a labelled line on every tick of its [`TickStep`] and a light line
+/// every day
+fn date_axis(span: &DateSpan) -> DateAxis {
+    let step = TickStep::for_span(span);
+    // A single day still needs some width
+    let end = span.end.max(span.start + Days::new(1));
+    (span.start..end)
+        .with_key_points(span.ticks(step))
+        .with_light_points(span.dates())
+}
+
+pub fn build_chart<'b, DB: DrawingBackend>(
+    root: &'b DrawingArea<DB, Shift>,
     settings: &TimeFrame<ObjTimeFrameSettings>,
-) -> Result<LineChartSettings<'b>, String> {
-    let s: &ObjTimeFrameSettings = match settings {
-        TimeFrame::Year(y) => y,
-        TimeFrame::Month(m) => m,
-    };
+) -> Result<LineChartSettings<'b, DB>, String> {
+    let s: &ObjTimeFrameSettings = settings.inner();
 
-    let mut chart = ChartBuilder::on(&root)
+    let chart = ChartBuilder::on(root)
         // Set the caption of the chart
-        .caption(&s.doc_title, ("sans-serif", 40).into_font())
+        .caption(&s.doc_title, s.theme.text(40))
         // Set the size of the label region
         .x_label_area_size(20)
-        .y_label_area_size(40)
+        .y_label_area_size(60)
+        // Room for the last date label
+        .margin_right(30)
         // Finally attach a coordinate on the drawing area and make a chart context
-        .build_cartesian_2d(1.0..s.x_axis_size as f32, s.y_range.0..s.y_range.1 as f32)
+        .build_cartesian_2d(date_axis(&s.span), s.y_range.0..s.y_range.1)
         .unwrap();
     Ok(chart)
 }
 
-pub fn style_chart<'a>(
-    mut chart: LineChartSettings<'a>,
+pub fn style_chart<'a, DB: DrawingBackend>(
+    mut chart: LineChartSettings<'a, DB>,
     settings: &TimeFrame<ObjTimeFrameSettings>,
-) -> LineChartSettings<'a> {
-    let s: &ObjTimeFrameSettings = match settings {
-        TimeFrame::Year(y) => y,
-        TimeFrame::Month(m) => m,
-    };
+) -> LineChartSettings<'a, DB> {
+    let s: &ObjTimeFrameSettings = settings.inner();
+    let t = &s.theme;
+    let foreground = t.colours().foreground;
+    let step = TickStep::for_span(&s.span);
 
     chart
         .configure_mesh()
-        // We can customize the maximum number of labels allowed for each axis
-        .x_labels(s.x_axis_size as usize)
+        .x_label_formatter(&|date| step.label(*date, &s.span))
+        // We can customize the maximum number of labels allowed for the y axis
         .y_labels(s.y_range.1 as usize)
+        .y_desc(&s.y_title)
         // We can also change the format of the label text
         .y_label_formatter(&|x| format!("{:.3}", x))
+        .label_style(t.text(12))
+        .axis_style(foreground)
+        .bold_line_style(foreground.mix(0.2))
+        .light_line_style(foreground.mix(0.1))
         .draw()
         .unwrap();
     chart
 }
 
-pub fn draw_data<'a>(
-    mut chart: LineChartSettings<'a>,
-    hdps: &Vec<HabitDayPerformance>,
+pub fn draw_data<'a, DB: DrawingBackend>(
+    mut chart: LineChartSettings<'a, DB>,
+    hdps: &[HabitDayPerformance],
     settings: &TimeFrame<ObjTimeFrameSettings>,
-) -> LineChartSettings<'a> {
-    let s: &ObjTimeFrameSettings = match settings {
-        TimeFrame::Year(y) => y,
-        TimeFrame::Month(m) => m,
-    };
+) -> LineChartSettings<'a, DB> {
+    let s: &ObjTimeFrameSettings = settings.inner();
+    let colour = s.theme.colours().series;
+    let series_data = day_values(hdps, s);
 
-    let series_data = hdps
+    // Days with several readings also show each one behind the day value
+    let readings = hdps
         .iter()
-        .enumerate()
-        .map(|(index, x)| {
-            let x_val = index as f32 + s.x_offset as f32;
-            let obj_title = s.tf_name.clone();
-
-            let obj = x.objective_performance.iter().find_map(|v| {
-                let task = v.task();
-                let title = match task {
-                    Task::Objective(t) => t,
-                    _ => {
-                        panic!("obj not found")
-                    }
-                };
-                println!("{title} {obj_title}");
-                if title == obj_title {
-                    Some(v)
-                } else {
-                    None
-                }
-            });
-
-            let y_val = match obj.unwrap().performance() {
-                Performance::Score(ps) => ps,
-                _ => {
-                    panic!("not type score")
-                }
-            };
-
-            (x_val, y_val)
+        .flat_map(|x| {
+            let x_val = x.date();
+            let values = x.objective_values(&s.tf_name);
+            let several = values.len() > 1;
+            values
+                .into_iter()
+                .filter(move |_| several)
+                .map(move |v| (x_val, v))
         })
-        .collect::<Vec<(f32, f32)>>();
+        .collect::<Vec<(NaiveDate, f32)>>();
+    chart
+        .draw_series(
+            readings
+                .into_iter()
+                .map(|c| Circle::new(c, 3, colour.mix(0.4).stroke_width(1))),
+        )
+        .unwrap();
 
     chart
-        .draw_series(LineSeries::new(series_data.clone(), &RED))
+        .draw_series(LineSeries::new(series_data.clone(), &colour))
         .unwrap();
     // Similarly, we can draw point series
     chart
         .draw_series(PointSeries::of_element(
             series_data,
             5,
-            &RED,
+            &colour,
             &|c, s, st| {
-                return EmptyElement::at(c)    // We want to construct a composed element on-the-fly
-            + Circle::new((0,0),s,st.filled()); // At this point, the new pixel coordinate is established
+                EmptyElement::at(c)    // We want to construct a composed element on-the-fly
+            + Circle::new((0,0),s,st.filled()) // At this point, the new pixel coordinate is established
             },
         ))
         .unwrap();
     chart
 }
+
+/// Date and value of every day in `hdps` with the objective of `s`
+fn day_values(hdps: &[HabitDayPerformance], s: &ObjTimeFrameSettings) -> Vec<(NaiveDate, f32)> {
+    // Imported days don't always carry every objective
+    hdps.iter()
+        .filter_map(|x| Some((x.date(), x.objective_by(&s.tf_name, s.aggregate)?)))
+        .collect()
+}
+
+/// Axis title style in `colour`, so each axis reads as the line of the same colour
+fn axis_title(theme: &Theme, colour: RGBColor) -> TextStyle<'_> {
+    let mut style = theme.text(20);
+    style.color = colour.to_backend_color();
+    style
+}
+
+/// Two objectives over the same days, `left` on the left axis in the series colour and
+/// `right` on the right axis in the overlay colour
+pub fn draw_overlay<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    doc_title: &str,
+    hdps: &[HabitDayPerformance],
+    left: &ObjTimeFrameSettings,
+    right: &ObjTimeFrameSettings,
+) {
+    let t = &left.theme;
+    let p = t.colours();
+    let step = TickStep::for_span(&left.span);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(doc_title, t.text(40))
+        .x_label_area_size(20)
+        .y_label_area_size(60)
+        .right_y_label_area_size(60)
+        .build_cartesian_2d(date_axis(&left.span), left.y_range.0..left.y_range.1)
+        .unwrap()
+        .set_secondary_coord(date_axis(&left.span), right.y_range.0..right.y_range.1);
+
+    chart
+        .configure_mesh()
+        .x_label_formatter(&|date| step.label(*date, &left.span))
+        .y_desc(&left.y_title)
+        .y_label_formatter(&|y| format!("{y:.1}"))
+        .label_style(t.text(12))
+        .axis_desc_style(axis_title(t, p.series))
+        .axis_style(p.foreground)
+        .bold_line_style(p.foreground.mix(0.2))
+        .light_line_style(p.foreground.mix(0.1))
+        .draw()
+        .unwrap();
+    chart
+        .configure_secondary_axes()
+        .y_desc(&right.y_title)
+        .y_label_formatter(&|y| format!("{y:.1}"))
+        .label_style(t.text(12))
+        .axis_desc_style(axis_title(t, p.overlay))
+        .axis_style(p.foreground)
+        .draw()
+        .unwrap();
+
+    let (series, overlay) = (p.series, p.overlay);
+    let left_data = day_values(hdps, left);
+    chart
+        .draw_series(LineSeries::new(left_data.clone(), &series))
+        .unwrap()
+        .label(&left.y_title)
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], series));
+    chart
+        .draw_series(
+            left_data
+                .into_iter()
+                .map(|c| Circle::new(c, 5, series.filled())),
+        )
+        .unwrap();
+
+    let right_data = day_values(hdps, right);
+    chart
+        .draw_secondary_series(LineSeries::new(right_data.clone(), &overlay))
+        .unwrap()
+        .label(&right.y_title)
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], overlay));
+    chart
+        .draw_secondary_series(
+            right_data
+                .into_iter()
+                .map(|c| TriangleMarker::new(c, 6, overlay.filled())),
+        )
+        .unwrap();
+
+    chart
+        .configure_series_labels()
+        .background_style(p.background.mix(0.8))
+        .border_style(p.foreground)
+        .label_font(t.text(20))
+        .draw()
+        .unwrap();
+}
+
+/// One small chart per objective stacked over the same days, dates only under the bottom
+/// one
+pub fn draw_grid<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    doc_title: &str,
+    hdps: &[HabitDayPerformance],
+    panels: &[TimeFrame<ObjTimeFrameSettings>],
+) {
+    let Some(first) = panels.first() else {
+        return;
+    };
+    let t = &first.inner().theme;
+    let root = root.titled(doc_title, t.text(40)).unwrap();
+    let areas = root.split_evenly((panels.len(), 1));
+
+    for (index, (area, settings)) in areas.iter().zip(panels).enumerate() {
+        let s = settings.inner();
+        let p = t.colours();
+        let step = TickStep::for_span(&s.span);
+        let bottom = index + 1 == panels.len();
+        let mut chart = ChartBuilder::on(area)
+            .margin(10)
+            .margin_right(30)
+            .x_label_area_size(if bottom { 20 } else { 0 })
+            .y_label_area_size(60)
+            .build_cartesian_2d(date_axis(&s.span), s.y_range.0..s.y_range.1)
+            .unwrap();
+        chart
+            .configure_mesh()
+            .x_label_formatter(&|date| step.label(*date, &s.span))
+            .y_labels(5)
+            .y_desc(&s.y_title)
+            .y_label_formatter(&|y| format!("{y:.1}"))
+            .label_style(t.text(12))
+            .axis_desc_style(t.text(16))
+            .axis_style(p.foreground)
+            .bold_line_style(p.foreground.mix(0.2))
+            .light_line_style(p.foreground.mix(0.1))
+            .draw()
+            .unwrap();
+        chart = draw_data(chart, hdps, settings);
+        draw_goal(chart, settings);
+    }
+}
+
+/// Label of an annotated day, its tags then its notes, cut short to fit the chart
+fn annotation(hdp: &HabitDayPerformance) -> String {
+    let tags = hdp.tags.iter().map(|t| format!(":{t}:"));
+    let mut label = tags
+        .chain(hdp.notes.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if label.chars().count() > 40 {
+        label = label.chars().take(37).collect::<String>() + "...";
+    }
+    label
+}
+
+/// Marks every day with tags or notes with a line and its label along it
+pub fn draw_annotations<'a, DB: DrawingBackend>(
+    mut chart: LineChartSettings<'a, DB>,
+    hdps: &[HabitDayPerformance],
+    settings: &TimeFrame<ObjTimeFrameSettings>,
+) -> LineChartSettings<'a, DB> {
+    let s: &ObjTimeFrameSettings = settings.inner();
+    let colour = s.theme.colours().annotation;
+    let font = s
+        .theme
+        .text(16)
+        .transform(FontTransform::Rotate90)
+        .color(&colour);
+
+    for hdp in hdps
+        .iter()
+        .filter(|x| !x.tags.is_empty() || !x.notes.is_empty())
+    {
+        let x = hdp.date();
+        chart
+            .draw_series(LineSeries::new(
+                vec![(x, s.y_range.0), (x, s.y_range.1)],
+                colour.mix(0.5).stroke_width(1),
+            ))
+            .unwrap();
+        chart
+            .draw_series(std::iter::once(Text::new(
+                annotation(hdp),
+                (x, s.y_range.1),
+                font.clone(),
+            )))
+            .unwrap();
+    }
+    chart
+}
+
+pub fn draw_goal<'a, DB: DrawingBackend + 'a>(
+    mut chart: LineChartSettings<'a, DB>,
+    settings: &TimeFrame<ObjTimeFrameSettings>,
+) -> LineChartSettings<'a, DB> {
+    let s: &ObjTimeFrameSettings = settings.inner();
+    let goal = match &s.goal {
+        Some(goal) => goal,
+        None => return chart,
+    };
+    let (x_start, x_end) = (s.span.start, s.span.end);
+    let target = goal.goal.target;
+    let p = s.theme.colours();
+    let (goal_colour, trend_colour) = (p.goal, p.trend);
+
+    // Flat line at the target
+    chart
+        .draw_series(LineSeries::new(
+            vec![(x_start, target), (x_end, target)],
+            p.goal.stroke_width(2),
+        ))
+        .unwrap()
+        .label(format!("goal {target:.1}"))
+        .legend(move |(x, y)| {
+            PathElement::new(vec![(x, y), (x + 20, y)], goal_colour.stroke_width(2))
+        });
+
+    // Deadline marker when it falls inside the chart
+    if let Some(deadline) = goal.goal.deadline.filter(|d| s.span.contains(*d)) {
+        let x = deadline;
+        chart
+            .draw_series(LineSeries::new(
+                vec![(x, s.y_range.0), (x, s.y_range.1)],
+                p.goal.stroke_width(1),
+            ))
+            .unwrap()
+            .label(format!("deadline {deadline}"))
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], goal_colour));
+    }
+
+    // Current trend stretched across the chart
+    if let Some(trend) = &goal.trend {
+        let label = match goal.projected {
+            Some(projected) => format!("trend, projected {projected} ({})", goal.status),
+            None => format!("trend ({})", goal.status),
+        };
+        chart
+            .draw_series(LineSeries::new(
+                vec![
+                    (x_start, trend.value_at(x_start)),
+                    (x_end, trend.value_at(x_end)),
+                ],
+                p.trend.stroke_width(1),
+            ))
+            .unwrap()
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], trend_colour));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(p.background.mix(0.8))
+        .border_style(p.foreground)
+        .label_font(s.theme.text(20))
+        .draw()
+        .unwrap();
+    chart
+}
@@ -1,2 +1,6 @@
+//! Plotters drawing code behind the heatmaps, line charts, time of day and weekday charts
+
 pub mod heatmap;
 pub mod linechart;
+pub mod timeofday;
+pub mod weekday;
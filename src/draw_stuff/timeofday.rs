@@ -0,0 +1,84 @@
+use chrono::{NaiveTime, Timelike};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::clock_stuff::HabitTimes;
+use crate::theme_stuff::Theme;
+
+/// Hours since midnight, the x coordinate of a time
+fn hours(time: NaiveTime) -> f32 {
+    time.num_seconds_from_midnight() as f32 / 3600.0
+}
+
+/// One row per habit with a dot for every completion time and a cross at the usual time
+pub fn draw_times<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    doc_title: &str,
+    habits: &[HabitTimes],
+    theme: &Theme,
+) {
+    let p = theme.colours();
+    root.fill(&p.background).unwrap();
+    if habits.is_empty() {
+        root.titled(doc_title, theme.text(30)).unwrap();
+        root.draw_text("no completion times recorded", &theme.text(20), (40, 80))
+            .unwrap();
+        root.present().unwrap();
+        return;
+    }
+
+    // First habit on the top row
+    let last_row = habits.len() as i32 - 1;
+    let row = |index: usize| SegmentValue::CenterOf(last_row - index as i32);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(doc_title, theme.text(30))
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(220)
+        .build_cartesian_2d(0f32..24f32, (0..last_row).into_segmented())
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_y_mesh()
+        .label_style(theme.text(12))
+        .axis_style(p.foreground)
+        .bold_line_style(p.foreground.mix(0.2))
+        .light_line_style(p.foreground.mix(0.1))
+        .x_labels(13)
+        .x_label_formatter(&|x| format!("{:02}:00", *x as u32))
+        .y_labels(habits.len())
+        .y_label_formatter(&|y| {
+            let idx = match y {
+                SegmentValue::CenterOf(idx) => *idx,
+                _ => return "".to_string(),
+            };
+            habits[(last_row - idx) as usize].title.clone()
+        })
+        .y_label_style(theme.text(15))
+        .draw()
+        .unwrap();
+
+    for (index, habit) in habits.iter().enumerate() {
+        chart
+            .draw_series(
+                habit
+                    .times
+                    .iter()
+                    .map(|t| Circle::new((hours(*t), row(index)), 4, p.marker.mix(0.4).filled())),
+            )
+            .unwrap();
+        if let Some(typical) = habit.typical() {
+            chart
+                .draw_series(std::iter::once(Cross::new(
+                    (hours(typical), row(index)),
+                    8,
+                    p.foreground.stroke_width(2),
+                )))
+                .unwrap();
+        }
+    }
+
+    root.present().unwrap();
+}
@@ -0,0 +1,172 @@
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+
+use crate::draw_stuff::heatmap::blend;
+use crate::theme_stuff::{contrasting, Theme};
+use crate::weekday_stuff::{WeekdayRates, WeekdayValues, WEEKDAYS};
+
+/// Weekday of a segmented x coordinate, Monday at 0
+fn weekday_label(x: &SegmentValue<i32>) -> String {
+    match x {
+        SegmentValue::CenterOf(idx) => WEEKDAYS
+            .get(*idx as usize)
+            .map_or("".to_string(), |day| day.to_string()),
+        _ => "".to_string(),
+    }
+}
+
+/// One row per habit and one column per weekday, each cell coloured from missed to done by
+/// its completion rate with the rate written on it
+pub fn draw_rates<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    doc_title: &str,
+    habits: &[WeekdayRates],
+    theme: &Theme,
+) {
+    let p = theme.colours();
+    root.fill(&p.background).unwrap();
+    // First habit on the top row
+    let last_row = habits.len().max(1) as i32 - 1;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(doc_title, theme.text(30))
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(220)
+        .build_cartesian_2d((0..6).into_segmented(), (0..last_row).into_segmented())
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .axis_style(p.foreground)
+        .x_labels(7)
+        .x_label_formatter(&weekday_label)
+        .x_label_style(theme.text(15))
+        .y_labels(habits.len())
+        .y_label_formatter(&|y| match y {
+            SegmentValue::CenterOf(idx) => habits
+                .get((last_row - idx) as usize)
+                .map_or("".to_string(), |h| h.title.clone()),
+            _ => "".to_string(),
+        })
+        .y_label_style(theme.text(15))
+        .draw()
+        .unwrap();
+
+    for (index, habit) in habits.iter().enumerate() {
+        let y = last_row - index as i32;
+        for (x, day) in WEEKDAYS.into_iter().enumerate() {
+            let x = x as i32;
+            let rate = habit.rate(day);
+            let color = rate.map_or(p.not_scheduled, |r| blend(p.missed, p.done, r as f64));
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [
+                        (SegmentValue::Exact(x), SegmentValue::Exact(y)),
+                        (SegmentValue::Exact(x + 1), SegmentValue::Exact(y + 1)),
+                    ],
+                    ShapeStyle::from(&color).filled(),
+                )))
+                .unwrap();
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [
+                        (SegmentValue::Exact(x), SegmentValue::Exact(y)),
+                        (SegmentValue::Exact(x + 1), SegmentValue::Exact(y + 1)),
+                    ],
+                    p.foreground.stroke_width(1),
+                )))
+                .unwrap();
+            if let Some(rate) = rate {
+                let mut style = theme.text(15).pos(Pos::new(HPos::Center, VPos::Center));
+                style.color = contrasting(color).to_backend_color();
+                chart
+                    .draw_series(std::iter::once(Text::new(
+                        format!("{:.0}%", rate * 100.0),
+                        (SegmentValue::CenterOf(x), SegmentValue::CenterOf(y)),
+                        style,
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+
+    root.present().unwrap();
+}
+
+/// A bar at the mean of each weekday with the single day values dotted over it
+pub fn draw_values<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    doc_title: &str,
+    y_title: &str,
+    y_range: (f32, f32),
+    values: &WeekdayValues,
+    theme: &Theme,
+) {
+    let p = theme.colours();
+    root.fill(&p.background).unwrap();
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(doc_title, theme.text(30))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d((0..6).into_segmented(), y_range.0..y_range.1)
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .label_style(theme.text(12))
+        .axis_style(p.foreground)
+        .bold_line_style(p.foreground.mix(0.2))
+        .light_line_style(p.foreground.mix(0.1))
+        .x_labels(7)
+        .x_label_formatter(&|x| {
+            let idx = match x {
+                SegmentValue::CenterOf(idx) => *idx as usize,
+                _ => return "".to_string(),
+            };
+            match values.values.get(idx) {
+                Some(days) => format!("{} ({})", weekday_label(x), days.len()),
+                None => "".to_string(),
+            }
+        })
+        .x_label_style(theme.text(15))
+        .y_desc(y_title)
+        .axis_desc_style(theme.text(20))
+        .draw()
+        .unwrap();
+
+    // One series per bar, a histogram over several bars draws them in hash order
+    for (x, day) in WEEKDAYS.into_iter().enumerate() {
+        let Some(mean) = values.mean(day) else {
+            continue;
+        };
+        chart
+            .draw_series(
+                Histogram::vertical(&chart)
+                    .style(p.marker.mix(0.4).filled())
+                    .margin(20)
+                    .baseline(y_range.0)
+                    .data([(x as i32, mean)]),
+            )
+            .unwrap();
+    }
+
+    for (x, days) in values.values.iter().enumerate() {
+        chart
+            .draw_series(days.iter().map(|v| {
+                Circle::new(
+                    (SegmentValue::CenterOf(x as i32), *v),
+                    4,
+                    p.foreground.mix(0.4).filled(),
+                )
+            }))
+            .unwrap();
+    }
+
+    root.present().unwrap();
+}
@@ -0,0 +1,302 @@
+//! Objective goals and the trends projected towards them
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::data_stuff::{Aggregate, HabitDayPerformance};
+
+/// How many days back from the latest value the trend is fitted over
+pub const TREND_DAYS: i64 = 28;
+
+/// Which way an objective has to move to hit its target
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+/// Target for an objective, declared in the config
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Goal {
+    pub target: f32,
+    pub deadline: Option<NaiveDate>,
+    pub direction: Direction,
+}
+
+impl Goal {
+    pub fn is_reached(&self, value: f32) -> bool {
+        match self.direction {
+            Direction::Up => value >= self.target,
+            Direction::Down => value <= self.target,
+        }
+    }
+}
+
+/// Straight line fitted through dated values, `value = intercept + slope * days since origin`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trend {
+    pub origin: NaiveDate,
+    pub slope: f32,
+    pub intercept: f32,
+}
+
+impl Trend {
+    /// Least squares fit, needs values on at least two different days
+    pub fn fit(points: &[(NaiveDate, f32)]) -> Option<Trend> {
+        let origin = points.first()?.0;
+        let xs: Vec<f32> = points
+            .iter()
+            .map(|(d, _)| (*d - origin).num_days() as f32)
+            .collect();
+        let n = points.len() as f32;
+        let mean_x = xs.iter().sum::<f32>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f32>() / n;
+
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (x, (_, y)) in xs.iter().zip(points) {
+            covariance += (x - mean_x) * (y - mean_y);
+            variance += (x - mean_x) * (x - mean_x);
+        }
+        if variance == 0.0 {
+            return None;
+        }
+        let slope = covariance / variance;
+        Some(Trend {
+            origin,
+            slope,
+            intercept: mean_y - slope * mean_x,
+        })
+    }
+
+    pub fn value_at(&self, date: NaiveDate) -> f32 {
+        self.intercept + self.slope * (date - self.origin).num_days() as f32
+    }
+
+    /// Whether the line moves the way `goal` needs it to
+    pub fn heads_to(&self, goal: &Goal) -> bool {
+        match goal.direction {
+            Direction::Up => self.slope > 0.0,
+            Direction::Down => self.slope < 0.0,
+        }
+    }
+
+    /// Day the line crosses `target` counting on from `from`, the last day with a value, and
+    /// never before `today`. None if it heads the wrong way or the day is past any date.
+    pub fn reaches(&self, goal: &Goal, from: NaiveDate, today: NaiveDate) -> Option<NaiveDate> {
+        if !self.heads_to(goal) {
+            return None;
+        }
+        let days = ((goal.target - self.value_at(from)) / self.slope)
+            .ceil()
+            .max(0.0);
+        let reached = from.checked_add_days(Days::new(days as u64))?;
+        Some(reached.max(today))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum GoalStatus {
+    Reached,
+    OnTrack,
+    OffTrack,
+    /// Fewer than two days of values to fit a trend through
+    NotEnoughData,
+}
+
+impl fmt::Display for GoalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoalStatus::Reached => write!(f, "reached"),
+            GoalStatus::OnTrack => write!(f, "on track"),
+            GoalStatus::OffTrack => write!(f, "off track"),
+            GoalStatus::NotEnoughData => write!(f, "not enough data"),
+        }
+    }
+}
+
+/// Where an objective stands against its goal
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GoalProgress {
+    pub objective: String,
+    pub goal: Goal,
+    pub current: Option<f32>,
+    pub trend: Option<Trend>,
+    pub projected: Option<NaiveDate>,
+    pub status: GoalStatus,
+}
+
+impl fmt::Display for GoalProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.objective)?;
+        match self.current {
+            Some(current) => write!(f, "{current:.1} -> {:.1}", self.goal.target)?,
+            None => write!(f, "no values -> {:.1}", self.goal.target)?,
+        }
+        if let Some(deadline) = self.goal.deadline {
+            write!(f, " by {deadline}")?;
+        }
+        match (self.status, self.projected) {
+            (GoalStatus::Reached, _) | (GoalStatus::NotEnoughData, _) => {}
+            (_, Some(projected)) => write!(f, ", projected {projected}")?,
+            (_, None) => match &self.trend {
+                Some(trend) if trend.heads_to(&self.goal) => write!(f, ", not within range")?,
+                _ => write!(f, ", trend heading the wrong way")?,
+            },
+        }
+        write!(f, ", {}", self.status)
+    }
+}
+
+/// Checks `objective` against `goal` using the day values in `hdps`, trend fitted over the
+/// last [`TREND_DAYS`] days that have a value and projected no earlier than `today`
+pub fn goal_progress(
+    hdps: &[HabitDayPerformance],
+    objective: &str,
+    goal: &Goal,
+    aggregate: Aggregate,
+    today: NaiveDate,
+) -> GoalProgress {
+    let points: Vec<(NaiveDate, f32)> = hdps
+        .iter()
+        .filter_map(|h| Some((h.date(), h.objective_by(objective, aggregate)?)))
+        .collect();
+    let current = points.last().map(|(_, v)| *v);
+    let recent: Vec<(NaiveDate, f32)> = match points.last() {
+        Some((last, _)) => points
+            .iter()
+            .filter(|(d, _)| (*last - *d).num_days() < TREND_DAYS)
+            .cloned()
+            .collect(),
+        None => vec![],
+    };
+    let trend = Trend::fit(&recent);
+    let projected = match (&trend, points.last()) {
+        (Some(trend), Some((last, _))) => trend.reaches(goal, *last, today),
+        _ => None,
+    };
+
+    let status = match (current, &trend) {
+        (Some(current), _) if goal.is_reached(current) => GoalStatus::Reached,
+        (_, None) => GoalStatus::NotEnoughData,
+        (_, Some(_)) => match (projected, goal.deadline) {
+            (Some(p), Some(deadline)) if p <= deadline => GoalStatus::OnTrack,
+            (Some(_), None) => GoalStatus::OnTrack,
+            _ => GoalStatus::OffTrack,
+        },
+    };
+
+    GoalProgress {
+        objective: objective.to_string(),
+        goal: goal.clone(),
+        current,
+        trend,
+        projected,
+        status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_stuff::{Performance, Task, TaskPerformance};
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    /// Consecutive days from `start` with `Weight` logged as `values`
+    fn weights(start: &str, values: &[f32]) -> Vec<HabitDayPerformance> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let mut hdp = HabitDayPerformance::new(date(start) + Days::new(i as u64));
+                let task = Task::Objective("Weight".to_string());
+                hdp.objective_performance
+                    .push(TaskPerformance::new(task, Performance::Score(*value)));
+                hdp
+            })
+            .collect()
+    }
+
+    fn lose_to(target: f32, deadline: &str) -> Goal {
+        Goal {
+            target,
+            deadline: Some(date(deadline)),
+            direction: Direction::Down,
+        }
+    }
+
+    #[test]
+    fn trend_projects_when_the_goal_is_reached() {
+        let hdps = weights("2025-03-01", &[80.0, 79.0, 78.0]);
+        let progress = goal_progress(
+            &hdps,
+            "Weight",
+            &lose_to(75.0, "2025-03-10"),
+            Aggregate::First,
+            date("2025-03-03"),
+        );
+        assert_eq!(progress.projected, Some(date("2025-03-06")));
+        assert_eq!(
+            progress.to_string(),
+            "Weight: 78.0 -> 75.0 by 2025-03-10, projected 2025-03-06, on track"
+        );
+
+        let late = goal_progress(
+            &hdps,
+            "Weight",
+            &lose_to(75.0, "2025-03-05"),
+            Aggregate::First,
+            date("2025-03-03"),
+        );
+        assert_eq!(late.status, GoalStatus::OffTrack);
+        // Looking at old data doesn't project into the past
+        let today = goal_progress(
+            &hdps,
+            "Weight",
+            &lose_to(75.0, "2025-04-01"),
+            Aggregate::First,
+            date("2025-03-20"),
+        );
+        assert_eq!(today.projected, Some(date("2025-03-20")));
+    }
+
+    #[test]
+    fn trends_need_two_days_and_the_right_direction() {
+        let single = weights("2025-03-01", &[80.0]);
+        let progress = goal_progress(
+            &single,
+            "Weight",
+            &lose_to(75.0, "2025-03-10"),
+            Aggregate::First,
+            date("2025-03-01"),
+        );
+        assert_eq!(progress.status, GoalStatus::NotEnoughData);
+
+        let gaining = weights("2025-03-01", &[78.0, 79.0]);
+        let progress = goal_progress(
+            &gaining,
+            "Weight",
+            &lose_to(75.0, "2025-03-10"),
+            Aggregate::First,
+            date("2025-03-02"),
+        );
+        assert_eq!(progress.status, GoalStatus::OffTrack);
+        assert!(progress
+            .to_string()
+            .ends_with("trend heading the wrong way, off track"));
+
+        let reached = goal_progress(
+            &gaining,
+            "Weight",
+            &lose_to(80.0, "2025-03-10"),
+            Aggregate::First,
+            date("2025-03-02"),
+        );
+        assert_eq!(reached.status, GoalStatus::Reached);
+    }
+}
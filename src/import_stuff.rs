@@ -0,0 +1,261 @@
+//! Importing habit and scale data exported from other trackers
+
+use chrono::NaiveDate;
+use csv::StringRecord;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+use crate::data_stuff::{HabitDayPerformance, Performance, Task, TaskPerformance};
+use crate::source_stuff::MemorySource;
+
+/// Collects imported rows per day before they are turned into a source
+#[derive(Default, Debug, PartialEq)]
+pub struct Importer {
+    days: BTreeMap<NaiveDate, HabitDayPerformance>,
+}
+
+impl Importer {
+    pub fn new() -> Self {
+        Importer::default()
+    }
+
+    fn day(&mut self, date: NaiveDate) -> &mut HabitDayPerformance {
+        self.days
+            .entry(date)
+            .or_insert_with(|| HabitDayPerformance::new(date))
+    }
+
+    fn push_todo(&mut self, date: NaiveDate, title: &str, performance: Performance) {
+        let day = self.day(date);
+        // Exports can repeat a row, the first one wins
+        if day.todo_performance.iter().any(|t| t.title() == title) {
+            return;
+        }
+        let tp = TaskPerformance::new(Task::Todo(title.to_string()), performance);
+        day.todo_performance.push(tp);
+    }
+
+    fn push_objective(&mut self, date: NaiveDate, title: &str, performance: Performance) {
+        let day = self.day(date);
+        if day.objective_performance.iter().any(|t| t.title() == title) {
+            return;
+        }
+        let tp = TaskPerformance::new(Task::Objective(title.to_string()), performance);
+        day.objective_performance.push(tp);
+    }
+
+    /// Loop Habit Tracker `Checkmarks.csv`: a `Date` column followed by one column per habit.
+    /// `2` is a manual check, `0` an unchecked day, anything else (auto checks, skips,
+    /// unknown) is left out.
+    pub fn loop_checkmarks(&mut self, path: &Path) -> Result<(), String> {
+        let mut reader = open_csv(path, true)?;
+        let headers = reader
+            .headers()
+            .map_err(|e| format!("{}: {e}", path.display()))?
+            .clone();
+
+        for record in reader.records() {
+            let record = record.map_err(|e| format!("{}: {e}", path.display()))?;
+            let date = parse_date(record.get(0).unwrap_or_default())?;
+            for (title, value) in headers.iter().zip(record.iter()).skip(1) {
+                let title = title.trim();
+                if title.is_empty() {
+                    continue;
+                }
+                match value.trim() {
+                    "2" => self.push_todo(date, title, Performance::Done),
+                    "0" => self.push_todo(date, title, Performance::Fail),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Generic `date,habit,checked` export, one row per habit per day, with or without a
+    /// header row
+    pub fn habit_csv(&mut self, path: &Path) -> Result<(), String> {
+        for record in dated_rows(path)? {
+            let date = parse_date(record.get(0).unwrap_or_default())?;
+            let title = record.get(1).unwrap_or_default().trim();
+            let checked = record.get(2).unwrap_or_default();
+            if title.is_empty() {
+                continue;
+            }
+            self.push_todo(date, title, parse_checked(checked)?);
+        }
+        Ok(())
+    }
+
+    /// Scale `date,weight` export, with or without a header row, the weight is stored under
+    /// the `objective` title. Weights may carry a unit, e.g. `181.2 lb`.
+    pub fn scale_csv(&mut self, path: &Path, objective: &str) -> Result<(), String> {
+        for record in dated_rows(path)? {
+            let date = parse_date(record.get(0).unwrap_or_default())?;
+            let raw = record.get(1).unwrap_or_default().trim();
+            let performance = Performance::parse_objective(raw)
+                .ok_or(format!("{}: {raw} is not a number", path.display()))?;
+            self.push_objective(date, objective, performance);
+        }
+        Ok(())
+    }
+
+    pub fn into_source(self) -> MemorySource {
+        MemorySource::new(self.days.into_values().collect())
+    }
+}
+
+fn open_csv(path: &Path, has_headers: bool) -> Result<csv::Reader<File>, String> {
+    csv::ReaderBuilder::new()
+        .has_headers(has_headers)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Rows of an export starting with a date column, leaving out its header row if it has one
+fn dated_rows(path: &Path) -> Result<Vec<StringRecord>, String> {
+    let mut rows = open_csv(path, false)?
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    // A header names the date column where the data rows hold a date
+    let header = rows
+        .first()
+        .is_some_and(|row| parse_date(row.get(0).unwrap_or_default()).is_err());
+    if header {
+        rows.remove(0);
+    }
+    Ok(rows)
+}
+
+/// Accepts `2025-03-14` and anything that starts with it, like `2025-03-14 07:30:00`
+fn parse_date(raw: &str) -> Result<NaiveDate, String> {
+    let raw = raw.trim();
+    let date_part = raw.get(..10).unwrap_or(raw);
+    NaiveDate::parse_from_str(date_part, "%Y-%m-%d").map_err(|e| format!("bad date {raw}: {e}"))
+}
+
+fn parse_checked(raw: &str) -> Result<Performance, String> {
+    match raw.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "y" | "x" | "done" => Ok(Performance::Done),
+        "0" | "false" | "no" | "n" | "" | "fail" => Ok(Performance::Fail),
+        other => Err(format!("can't tell if {other} means checked")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_stuff::{ConflictRule, HabitProgress};
+    use crate::source_stuff::HabitSource;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    /// `contents` written to a file in the temp dir
+    fn export(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("org_analyzer_{}_{name}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn imported(importer: Importer) -> HabitProgress {
+        importer.into_source().load_all()
+    }
+
+    fn performance(hdp: &HabitDayPerformance, title: &str) -> Option<Performance> {
+        hdp.todo_performance
+            .iter()
+            .chain(&hdp.objective_performance)
+            .find(|t| t.title() == title)
+            .map(|t| t.performance())
+    }
+
+    #[test]
+    fn dates_may_carry_a_time() {
+        assert_eq!(parse_date("2025-03-14 07:30:00"), Ok(date("2025-03-14")));
+        assert!(parse_date("14/03/2025").is_err());
+        assert!(matches!(parse_checked("Yes"), Ok(Performance::Done)));
+        assert!(matches!(parse_checked(""), Ok(Performance::Fail)));
+        assert!(parse_checked("maybe").is_err());
+    }
+
+    #[test]
+    fn exports_are_read_with_or_without_headers() {
+        let loop_csv = export(
+            "Checkmarks.csv",
+            "Date,Walk,Swim,\n2025-03-03,2,0,\n2025-03-04,1,3,\n",
+        );
+        let habits = export(
+            "habits.csv",
+            "2025-03-03,Stretch,x\n2025-03-03,Stretch,no\n",
+        );
+        let scale = export("scale.csv", "date,weight\n2025-03-03,82.4\n");
+        let mut importer = Importer::new();
+        importer.loop_checkmarks(&loop_csv).unwrap();
+        importer.habit_csv(&habits).unwrap();
+        importer.scale_csv(&scale, "Weight=").unwrap();
+        for path in [loop_csv, habits, scale] {
+            fs::remove_file(path).unwrap();
+        }
+
+        let progress = imported(importer);
+        let monday = &progress.hpds()[0];
+        assert_eq!(monday.date(), date("2025-03-03"));
+        assert!(matches!(
+            performance(monday, "Walk"),
+            Some(Performance::Done)
+        ));
+        assert!(matches!(
+            performance(monday, "Swim"),
+            Some(Performance::Fail)
+        ));
+        // A repeated row doesn't override the first one
+        assert!(matches!(
+            performance(monday, "Stretch"),
+            Some(Performance::Done)
+        ));
+        assert!(matches!(
+            performance(monday, "Weight="),
+            Some(Performance::Score(w)) if w == 82.4
+        ));
+        // Auto checks and skips are left out, leaving nothing for the next day
+        assert_eq!(progress.hpds().len(), 1);
+    }
+
+    #[test]
+    fn conflicts_follow_the_rule() {
+        let path = export("conflict.csv", "2025-03-03,Walk,yes\n2025-03-03,Swim,no\n");
+        let mut org = Importer::new();
+        org.push_todo(date("2025-03-03"), "Walk", Performance::Fail);
+        org.push_todo(date("2025-03-03"), "Swim", Performance::Done);
+        let org = imported(org);
+
+        for (rule, walk, swim) in [
+            (ConflictRule::KeepOrg, false, true),
+            (ConflictRule::KeepImport, true, false),
+            (ConflictRule::AnyDone, true, true),
+        ] {
+            let mut import = Importer::new();
+            import.habit_csv(&path).unwrap();
+            let mut merged = HabitProgress(org.hpds().clone());
+            merged.merge(imported(import), rule);
+            let day = &merged.hpds()[0];
+            assert_eq!(
+                matches!(performance(day, "Walk"), Some(Performance::Done)),
+                walk
+            );
+            assert_eq!(
+                matches!(performance(day, "Swim"), Some(Performance::Done)),
+                swim
+            );
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
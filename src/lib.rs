@@ -0,0 +1,46 @@
+//! Reads habit and objective tracking out of org day files and turns it into stats,
+//! charts, reviews and alerts.
+//!
+//! - Parsing: [`data_stuff::process_org_file`] reads one day file,
+//!   [`source_stuff::OrgDirSource`] a whole `{year}/{Month}/{DD}.org` tree and
+//!   [`import_stuff::Importer`] data exported from other trackers. [`lint_stuff`] points out
+//!   lines that wouldn't parse the way they were meant to.
+//! - Data model: [`data_stuff::HabitProgress`] holds one [`data_stuff::HabitDayPerformance`]
+//!   per day, [`time_stuff`] cuts it into weeks, months, quarters and years,
+//!   [`query_stuff`] narrows it down by day tags and [`unit_stuff`] converts objective values
+//!   between units.
+//! - Analytics: [`schedule_stuff`] scores habits against their schedules, [`clock_stuff`]
+//!   works out when habits get done, [`weekday_stuff`] compares the days of the week,
+//!   [`goal_stuff`] fits objective trends and
+//!   [`alert_stuff`] checks alert rules, all driven by a [`config_stuff::Config`].
+//! - Rendering: [`analyze_stuff::Analyzer`] draws heatmaps and line charts onto any plotters
+//!   backend in the colours and fonts of a [`theme_stuff::Theme`], [`write_stuff`] writes
+//!   org files and [`serve_stuff`] serves both over HTTP.
+//!
+//! ```
+//! use org_analyzer::data_stuff::process_org_file;
+//!
+//! let day = "** DONE /HABIT/ Walk\n** /OBJECTIVE/ Weight=\n- 82.4\n";
+//! let parsed = process_org_file(day.to_string());
+//! assert_eq!(parsed[0][0].title(), "Walk");
+//! assert_eq!(parsed[1][0].title(), "Weight=");
+//! ```
+
+pub mod alert_stuff;
+pub mod analyze_stuff;
+pub mod clock_stuff;
+pub mod config_stuff;
+pub mod data_stuff;
+pub mod draw_stuff;
+pub mod goal_stuff;
+pub mod import_stuff;
+pub mod lint_stuff;
+pub mod query_stuff;
+pub mod schedule_stuff;
+pub mod serve_stuff;
+pub mod source_stuff;
+pub mod theme_stuff;
+pub mod time_stuff;
+pub mod unit_stuff;
+pub mod weekday_stuff;
+pub mod write_stuff;
@@ -0,0 +1,280 @@
+//! Checks the notes for mistakes that would otherwise silently drop data
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use crate::config_stuff::Config;
+use crate::data_stuff::day_file_path;
+use crate::time_stuff::{month_name_to_num, month_num_to_name};
+use crate::unit_stuff::parse_value;
+
+/// Folders inside the notes that hold other org files than days
+const NON_DAY_DIRS: [&str; 1] = ["reviews"];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LintKind {
+    /// Habit title that isn't in the known list, with the closest known title if one is near
+    UnknownHabit {
+        title: String,
+        suggestion: Option<String>,
+    },
+    /// Habit listed a second time in the same day, `first_line` is where it first appears
+    DuplicateHabit { title: String, first_line: usize },
+    /// `/OBJECTIVE/` heading without a `- <number>` line under it
+    MissingValue { objective: String },
+    /// Value outside the `(min-max)` range written in the objective title
+    OutOfRange {
+        objective: String,
+        value: f32,
+        range: (f32, f32),
+    },
+    /// Org file that isn't where its date says it should be, or not at a day path at all
+    WrongFolder { expected: Option<PathBuf> },
+    /// Day file without a single `/HABIT/` line
+    NoHabits,
+}
+
+/// A problem at a line of a file, the path is relative to the notes root
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LintIssue {
+    pub path: PathBuf,
+    pub line: usize,
+    pub kind: LintKind,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.path.display(), self.line)?;
+        match &self.kind {
+            LintKind::UnknownHabit { title, suggestion } => {
+                write!(f, "unknown habit \"{title}\"")?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean \"{suggestion}\"?")?;
+                }
+                Ok(())
+            }
+            LintKind::DuplicateHabit { title, first_line } => {
+                write!(f, "habit \"{title}\" already listed on line {first_line}")
+            }
+            LintKind::MissingValue { objective } => {
+                write!(f, "objective \"{objective}\" has no \"- <number>\" line")
+            }
+            LintKind::OutOfRange {
+                objective,
+                value,
+                range,
+            } => write!(
+                f,
+                "objective \"{objective}\" value {value} is outside {}-{}",
+                range.0, range.1
+            ),
+            LintKind::WrongFolder {
+                expected: Some(expected),
+            } => write!(f, "day file belongs at {}", expected.display()),
+            LintKind::WrongFolder { expected: None } => {
+                write!(
+                    f,
+                    "not at a {{year}}/{{Month}}/{{DD}}.org path, it won't be read"
+                )
+            }
+            LintKind::NoHabits => write!(f, "day has no habits"),
+        }
+    }
+}
+
+/// Edit distance between two titles, ignoring case
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Known title close enough to `title` to be what was meant
+fn closest<'a>(title: &str, known: &[&'a str]) -> Option<&'a str> {
+    let (best, d) = known
+        .iter()
+        .map(|k| (*k, distance(title, k)))
+        .min_by_key(|(_, d)| *d)?;
+    (d <= (title.chars().count() / 4).max(2)).then_some(best)
+}
+
+static HABIT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\*+\s+(?:TODO|DONE|FAIL)\s+/HABIT/\s+(.*?)(?:\s+:[^\s:]+(?::[^\s:]+)*:)?\s*$")
+        .unwrap()
+});
+static OBJECTIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/OBJECTIVE/\s+(.*)$").unwrap());
+static VALUE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*-\s+(.*)$").unwrap());
+static RANGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\((\d+(?:\.\d+)?)-(\d+(?:\.\d+)?)\)").unwrap());
+
+/// `(min, max)` from an objective title like `Mood(0-10)=`
+fn title_range(objective: &str) -> Option<(f32, f32)> {
+    let cap = RANGE_RE.captures(objective)?;
+    Some((cap[1].parse().ok()?, cap[2].parse().ok()?))
+}
+
+/// Date a path relative to the notes root stands for, if it is a day file path
+fn path_date(relative: &Path) -> Option<NaiveDate> {
+    let parts: Vec<&str> = relative.iter().filter_map(|p| p.to_str()).collect();
+    let [year, month, file] = parts.as_slice() else {
+        return None;
+    };
+    let day = file.strip_suffix(".org")?;
+    if !(1..=12).any(|m| month_num_to_name(m) == *month) {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(
+        year.parse().ok()?,
+        month_name_to_num(month),
+        day.parse().ok()?,
+    )
+}
+
+/// Date in a `#+TITLE: Wednesday, March 05 2025` line, as written by `new-day`
+fn title_date(content: &str) -> Option<NaiveDate> {
+    let title = content
+        .lines()
+        .find_map(|l| l.strip_prefix("#+TITLE:"))?
+        .trim();
+    NaiveDate::parse_from_str(title, "%A, %B %d %Y").ok()
+}
+
+/// Every org file under `root` outside the non day folders, sorted
+fn org_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let skip = dir == root
+                    && NON_DAY_DIRS
+                        .iter()
+                        .any(|d| path.file_name() == Some(d.as_ref()));
+                if !skip {
+                    dirs.push(path);
+                }
+            } else if path.extension().is_some_and(|e| e == "org") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Checks the habit and objective lines of one day file. Titles are compared after the
+/// config's aliases and normalisation, so an aliased title isn't reported as unknown.
+pub fn lint_day(relative: &Path, content: &str, config: &Config) -> Vec<LintIssue> {
+    let known = config.known_habits();
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut issues = vec![];
+    let mut issue = |line: usize, kind: LintKind| {
+        issues.push(LintIssue {
+            path: relative.to_path_buf(),
+            line,
+            kind,
+        })
+    };
+    let mut seen: Vec<(String, usize)> = vec![];
+
+    for (index, line) in lines.iter().enumerate() {
+        let number = index + 1;
+        if let Some(cap) = HABIT_RE.captures(line) {
+            let title = cap[1].trim().to_string();
+            let canonical = config.canonical_title(&title);
+            if let Some((_, first_line)) = seen.iter().find(|(t, _)| *t == canonical) {
+                let first_line = *first_line;
+                issue(number, LintKind::DuplicateHabit { title, first_line });
+                continue;
+            }
+            if !known.contains(&canonical.as_str()) {
+                let suggestion = closest(&title, &known).map(|s| s.to_string());
+                issue(
+                    number,
+                    LintKind::UnknownHabit {
+                        title: title.clone(),
+                        suggestion,
+                    },
+                );
+            }
+            seen.push((canonical, number));
+        } else if let Some(cap) = OBJECTIVE_RE.captures(line) {
+            let objective = cap[1].trim().to_string();
+            let values: Vec<f32> = lines[index + 1..]
+                .iter()
+                .take_while(|l| !l.starts_with('*'))
+                .filter_map(|l| VALUE_RE.captures(l))
+                .filter_map(|c| parse_value(&c[1]))
+                .map(|(value, _)| value)
+                .collect();
+            if values.is_empty() {
+                issue(number, LintKind::MissingValue { objective });
+                continue;
+            }
+            let Some(range) = title_range(&objective) else {
+                continue;
+            };
+            for value in values {
+                if value < range.0 || value > range.1 {
+                    let objective = objective.clone();
+                    issue(
+                        number,
+                        LintKind::OutOfRange {
+                            objective,
+                            value,
+                            range,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    if seen.is_empty() {
+        issue(1, LintKind::NoHabits);
+    }
+    issues
+}
+
+/// Lints every day file under `root`. Known habits are the built in titles plus the ones
+/// named in the config.
+pub fn lint_notes(root: &Path, config: &Config) -> Result<Vec<LintIssue>, String> {
+    let mut issues = vec![];
+    for path in org_files(root) {
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        let content = fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        let expected = title_date(&content).or(path_date(&relative));
+        let expected = expected.map(|d| day_file_path(Path::new(""), d));
+        if expected.as_ref() != Some(&relative) {
+            issues.push(LintIssue {
+                path: relative.clone(),
+                line: 1,
+                kind: LintKind::WrongFolder { expected },
+            });
+        }
+        issues.extend(lint_day(&relative, &content, config));
+    }
+    Ok(issues)
+}
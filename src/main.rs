@@ -1,43 +1,407 @@
-mod analyze_stuff;
-mod data_stuff;
-mod draw_stuff;
-mod time_stuff;
+use std::path::PathBuf;
 
-use crate::analyze_stuff::Analyzer;
+use chrono::{Days, Local, NaiveDate};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+
+use org_analyzer::analyze_stuff::{Analyzer, Overlay};
+use org_analyzer::config_stuff::{Config, CONFIG_FILE_NAME};
+use org_analyzer::data_stuff::{ConflictRule, HabitProgress};
+use org_analyzer::import_stuff::Importer;
+use org_analyzer::lint_stuff::lint_notes;
+use org_analyzer::query_stuff::{segments, DayFilter};
+use org_analyzer::source_stuff::{HabitSource, OrgDirSource, DEFAULT_NOTES_DIR};
+use org_analyzer::theme_stuff::PaletteName;
+use org_analyzer::time_stuff::{DateSpan, FrameKind, TimeFrame};
+use org_analyzer::write_stuff::{write_day_file, write_review};
+
+#[derive(Parser)]
+#[command(about = "Charts habit and objective progress from org day files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Root of the habit notes, laid out as {year}/{Month}/{DD}.org
+    #[arg(long, global = true, default_value = DEFAULT_NOTES_DIR)]
+    notes_dir: PathBuf,
+
+    /// Habit schedules and other settings, defaults to health-mode.toml in the notes dir
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// First day to read from the notes
+    #[arg(long, global = true, default_value = "2025-02-22")]
+    start: NaiveDate,
+
+    /// Loop Habit Tracker Checkmarks.csv export to merge in
+    #[arg(long, global = true)]
+    import_loop: Vec<PathBuf>,
+
+    /// CSV export with date,habit,checked rows to merge in
+    #[arg(long, global = true)]
+    import_csv: Vec<PathBuf>,
+
+    /// Scale CSV export with date,weight rows to merge in
+    #[arg(long, global = true)]
+    import_scale: Vec<PathBuf>,
+
+    /// Objective title scale weights are stored under
+    #[arg(long, global = true, default_value = "Weight=")]
+    scale_objective: String,
+
+    /// What wins when an import and the notes disagree: keep-org, keep-import or any-done
+    #[arg(long, global = true, default_value = "keep-org")]
+    on_conflict: ConflictRule,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Draw the habit heatmaps and objective charts (the default)
+    Report(ReportArgs),
+    /// Create a day file with every habit as TODO and empty objective slots
+    NewDay {
+        /// Day to create, tomorrow if left out
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+    /// Write an org weekly review into the notes' reviews folder
+    Review {
+        /// Any day in the week to review, today if left out
+        #[arg(long)]
+        date: Option<NaiveDate>,
+
+        /// Replace a review that already exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check the alert rules from the config against the latest days and send the ones firing
+    /// to the alert outputs, reports only list them
+    Alerts,
+    /// Check the notes for unknown habits, missing values and misplaced files
+    Lint,
+    /// Serve the habit data and charts over HTTP, reloading when the notes change
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+}
+
+#[derive(Args, Default)]
+struct ReportArgs {
+    /// Comma separated time frames to chart: week, month, quarter, year [default: month,year]
+    #[arg(long, value_delimiter = ',')]
+    frames: Vec<FrameKind>,
+
+    /// Also chart a custom range starting on this day, needs --to
+    #[arg(long, requires = "to")]
+    from: Option<NaiveDate>,
+
+    /// Last day of the custom range
+    #[arg(long, requires = "from")]
+    to: Option<NaiveDate>,
+
+    /// Only chart and count the days matching these tags, e.g. travel,!sick
+    #[arg(long)]
+    only: Option<DayFilter>,
+
+    /// Also chart and count the days with and without this tag separately and compare them
+    #[arg(long)]
+    segment: Option<String>,
+
+    /// Chart colours: light, colour-blind or dark [default: the config's theme]
+    #[arg(long)]
+    theme: Option<PaletteName>,
+
+    /// Comma separated pairs of objectives to chart together with an axis each, e.g.
+    /// weight:gerd
+    #[arg(long, value_delimiter = ',')]
+    overlay: Vec<Overlay>,
+}
+
+impl Cli {
+    fn load_config(&self) -> Result<Config, String> {
+        match &self.config {
+            Some(path) => Config::load(path),
+            None => Config::load_or_default(&self.notes_dir.join(CONFIG_FILE_NAME)),
+        }
+    }
+
+    fn load_progress(&self) -> Result<HabitProgress, String> {
+        let org = OrgDirSource::new(self.notes_dir.clone(), self.start);
+        let mut progress = org.load_all();
+
+        let mut importer = Importer::new();
+        for path in &self.import_loop {
+            importer.loop_checkmarks(path)?;
+        }
+        for path in &self.import_csv {
+            importer.habit_csv(path)?;
+        }
+        for path in &self.import_scale {
+            importer.scale_csv(path, &self.scale_objective)?;
+        }
+        progress.merge(importer.into_source().load_all(), self.on_conflict);
+        Ok(progress)
+    }
+
+    fn load_analyzer(&self) -> Result<Analyzer, String> {
+        let progress = self
+            .load_progress()
+            .map_err(|e| format!("habit data: {e}"))?;
+        let config = self.load_config().map_err(|e| format!("config: {e}"))?;
+        Ok(Analyzer::from_progress(progress).with_config(config))
+    }
+}
 
 fn main() {
-    let a = Analyzer::new();
-    match a.yearly_habit_performance() {
+    let cli = Cli::parse();
+    if let Some(Command::Report(args)) = &cli.command {
+        check_range(args);
+    }
+    match cli.command {
+        Some(Command::NewDay { date }) => new_day(&cli, date),
+        Some(Command::Review { date, force }) => review(&cli, date, force),
+        Some(Command::Alerts) => alerts(&cli),
+        Some(Command::Lint) => lint(&cli),
+        Some(Command::Serve { ref addr }) => serve(&cli, addr),
+        Some(Command::Report(ref args)) => report(&cli, args),
+        None => report(&cli, &ReportArgs::default()),
+    }
+}
+
+/// Stops with a usage error when the custom range ends before it starts
+fn check_range(args: &ReportArgs) {
+    if let (Some(from), Some(to)) = (args.from, args.to) {
+        if from > to {
+            Cli::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!("--from {from} is after --to {to}"),
+                )
+                .exit();
+        }
+    }
+}
+
+fn new_day(cli: &Cli, date: Option<NaiveDate>) {
+    let date = date.unwrap_or_else(|| Local::now().date_naive() + Days::new(1));
+    let a = analyzer(cli);
+    let habits = a.day_habits(date);
+    let objectives = a.config().known_objectives();
+    match write_day_file(&cli.notes_dir, date, &habits, &objectives) {
+        Ok(path) => {
+            println!("created {}", path.display());
+        }
+        Err(e) => {
+            panic!("new day failed: {e}")
+        }
+    }
+}
+
+fn analyzer(cli: &Cli) -> Analyzer {
+    match cli.load_analyzer() {
+        Ok(a) => a,
+        Err(e) => {
+            panic!("loading failed: {e}")
+        }
+    }
+}
+
+fn review(cli: &Cli, date: Option<NaiveDate>, force: bool) {
+    let a = analyzer(cli);
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    let written = a
+        .weekly_review(date)
+        .and_then(|(label, org)| write_review(&cli.notes_dir, &label, &org, force));
+    match written {
+        Ok(path) => {
+            println!("wrote {}", path.display());
+        }
+        Err(e) => {
+            panic!("weekly review failed: {e}")
+        }
+    }
+}
+
+fn serve(cli: &Cli, addr: &str) {
+    match org_analyzer::serve_stuff::serve(addr, &cli.notes_dir, || cli.load_analyzer()) {
+        Ok(()) => {}
+        Err(e) => {
+            panic!("serve failed: {e}")
+        }
+    }
+}
+
+fn lint(cli: &Cli) {
+    let config = match cli.load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            panic!("loading config failed: {e}")
+        }
+    };
+    match lint_notes(&cli.notes_dir, &config) {
+        Ok(issues) => {
+            for issue in &issues {
+                println!("{issue}");
+            }
+            println!("{} issues in {}", issues.len(), cli.notes_dir.display());
+            if !issues.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            panic!("lint failed: {e}")
+        }
+    }
+}
+
+fn alerts(cli: &Cli) {
+    let a = analyzer(cli);
+    match a.alerts() {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(e) => {
+            panic!("alerts failed: {e}")
+        }
+    }
+}
+
+/// Charts and stats that follow the analyzer's day filter
+fn filtered_report(a: &Analyzer, frames: &[TimeFrame<DateSpan>], overlays: &[Overlay]) {
+    match a.habit_performance(frames, false) {
         Ok(res) => {
             println!("{res}");
         }
         Err(_e) => {
-            panic!("yearly failed")
+            panic!("habit heatmaps failed")
         }
     }
-    match a.monthly_habit_performance() {
+    match a.habit_performance(frames, true) {
         Ok(res) => {
             println!("{res}");
         }
         Err(_e) => {
-            panic!("monthly failed")
+            panic!("habit group heatmaps failed")
         }
     }
-    match a.yearly_objective_performance() {
+    match a.objective_performance(frames) {
         Ok(res) => {
             println!("{res}");
         }
         Err(_e) => {
-            panic!("yearly failed")
+            panic!("objective charts failed")
+        }
+    };
+
+    match a.combined_charts(frames, overlays) {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(e) => {
+            panic!("combined objective charts failed: {e}")
         }
     };
 
-    match a.monthly_objective_performance() {
+    match a.time_of_day(frames) {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(e) => {
+            panic!("time of day charts failed: {e}")
+        }
+    };
+
+    match a.weekday_charts(frames) {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(e) => {
+            panic!("weekday charts failed: {e}")
+        }
+    };
+
+    match a.habit_completion() {
         Ok(res) => {
             println!("{res}");
         }
         Err(_e) => {
-            panic!("monthly failed")
+            panic!("habit completion failed")
+        }
+    };
+
+    match a.clocked_summary() {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(e) => {
+            panic!("habit times failed: {e}")
+        }
+    };
+
+    match a.weekday_summary() {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(e) => {
+            panic!("weekday summary failed: {e}")
+        }
+    };
+}
+
+fn report(cli: &Cli, args: &ReportArgs) {
+    let mut a = analyzer(cli);
+    let filter = args.only.clone().unwrap_or_default();
+
+    let kinds = match args.frames.is_empty() {
+        true => vec![FrameKind::Month, FrameKind::Year],
+        false => args.frames.clone(),
+    };
+    let mut frames: Vec<TimeFrame<DateSpan>> = kinds.iter().flat_map(|k| a.frames(*k)).collect();
+    if let (Some(from), Some(to)) = (args.from, args.to) {
+        frames.push(TimeFrame::Range(DateSpan::new(from, to)));
+    }
+
+    if let Some(palette) = args.theme {
+        let mut theme = a.theme().clone();
+        theme.palette = palette;
+        a.set_theme(theme);
+    }
+    a.set_filter(filter.clone());
+    filtered_report(&a, &frames, &args.overlay);
+
+    if let Some(tag) = &args.segment {
+        for (_, segment) in segments(tag) {
+            a.set_filter(filter.and(&segment));
+            filtered_report(&a, &frames, &args.overlay);
+        }
+        a.set_filter(filter);
+        match a.segment_summary(tag) {
+            Ok(res) => {
+                println!("{res}");
+            }
+            Err(e) => {
+                panic!("segment summary failed: {e}")
+            }
+        };
+    }
+
+    match a.goal_summary() {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(_e) => {
+            panic!("goal summary failed")
+        }
+    };
+
+    match a.alert_summary() {
+        Ok(res) => {
+            println!("{res}");
+        }
+        Err(e) => {
+            panic!("alert summary failed: {e}")
         }
     };
 }
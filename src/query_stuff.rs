@@ -0,0 +1,88 @@
+//! Day filters and segmentations by tag that every report can be narrowed down with
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::data_stuff::HabitDayPerformance;
+
+/// Which days an analysis looks at, the days carrying every `with` tag and none of the
+/// `without` tags. An empty filter lets every day through.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayFilter {
+    pub with: Vec<String>,
+    pub without: Vec<String>,
+}
+
+impl DayFilter {
+    pub fn tagged(tag: &str) -> Self {
+        DayFilter {
+            with: vec![tag.to_string()],
+            without: vec![],
+        }
+    }
+
+    pub fn untagged(tag: &str) -> Self {
+        DayFilter {
+            with: vec![],
+            without: vec![tag.to_string()],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.with.is_empty() && self.without.is_empty()
+    }
+
+    pub fn matches(&self, hdp: &HabitDayPerformance) -> bool {
+        self.with.iter().all(|t| hdp.has_tag(t)) && !self.without.iter().any(|t| hdp.has_tag(t))
+    }
+
+    /// Days both filters let through
+    pub fn and(&self, other: &DayFilter) -> DayFilter {
+        let mut both = self.clone();
+        both.with.extend(other.with.iter().cloned());
+        both.without.extend(other.without.iter().cloned());
+        both
+    }
+
+    /// Filename safe form, e.g. `travel+no-sick`
+    pub fn file_label(&self) -> String {
+        let with = self.with.iter().cloned();
+        let without = self.without.iter().map(|t| format!("no-{t}"));
+        with.chain(without).collect::<Vec<_>>().join("+")
+    }
+}
+
+/// `travel,!sick`: tags to have, `!` or `-` in front of the ones to be without
+impl FromStr for DayFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = DayFilter::default();
+        for part in s.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            match part.strip_prefix(['!', '-']) {
+                Some("") => return Err(format!("{s}: {part} is missing its tag")),
+                Some(tag) => filter.without.push(tag.to_string()),
+                None => filter.with.push(part.to_string()),
+            }
+        }
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for DayFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let with = self.with.iter().cloned();
+        let without = self.without.iter().map(|t| format!("!{t}"));
+        write!(f, "{}", with.chain(without).collect::<Vec<_>>().join(","))
+    }
+}
+
+/// Splits the days by `tag` into the tagged days and the rest, named for reports
+pub fn segments(tag: &str) -> [(String, DayFilter); 2] {
+    [
+        (tag.to_string(), DayFilter::tagged(tag)),
+        (format!("not {tag}"), DayFilter::untagged(tag)),
+    ]
+}
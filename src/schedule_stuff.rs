@@ -0,0 +1,581 @@
+//! Habit schedules and scoring days against them
+
+use chrono::{Datelike, Days, IsoWeek, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use crate::config_stuff::Config;
+use crate::data_stuff::{habit_instance, HabitDayPerformance, Performance};
+
+/// Per habit title, the status of every day
+pub type StatusGrid = HashMap<String, BTreeMap<NaiveDate, HabitStatus>>;
+
+/// How often a habit is expected to happen
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Schedule {
+    /// Every day
+    #[default]
+    Daily,
+    /// Only on the listed weekdays, e.g. `days = ["Mon", "Thu"]`
+    Weekdays { days: Vec<Weekday> },
+    /// Any `times` days in each Monday to Sunday week
+    PerWeek { times: u32 },
+    /// Every `days` days counting from `from`
+    Every { days: u32, from: NaiveDate },
+}
+
+impl Schedule {
+    /// Whether a day based schedule expects the habit on `date`, per week schedules are always open
+    pub fn is_due(&self, date: NaiveDate) -> bool {
+        match self {
+            Schedule::Daily | Schedule::PerWeek { .. } => true,
+            Schedule::Weekdays { days } => days.contains(&date.weekday()),
+            Schedule::Every { days, from } => {
+                let since = (date - *from).num_days();
+                since >= 0 && since % (*days).max(1) as i64 == 0
+            }
+        }
+    }
+}
+
+/// What a single habit looked like on a single day once its schedule is taken into account
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum HabitStatus {
+    Done,
+    /// Marked FAIL on a day it was expected
+    Missed,
+    /// Expected but there is no entry for it in the day file
+    Unrecorded,
+    /// Not expected that day, or the weekly target was already met
+    NotScheduled,
+    /// Some of the `#N` instances of a habit got done, `(done, expected)`
+    Partial(u32, u32),
+    /// Before the habit was started or after it was retired
+    Inactive,
+}
+
+/// How long a habit has to be missing from the notes before it counts as retired
+pub const RETIRED_AFTER_DAYS: i64 = 28;
+
+/// The days a habit was being tracked
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Lifecycle {
+    /// Tracked from `start` until `retired`, open ended when either is missing
+    Active {
+        start: Option<NaiveDate>,
+        retired: Option<NaiveDate>,
+    },
+    /// Never recorded and not declared in the config
+    Never,
+}
+
+impl Lifecycle {
+    pub fn is_active(&self, date: NaiveDate) -> bool {
+        match self {
+            Lifecycle::Active { start, retired } => {
+                start.is_none_or(|s| date >= s) && retired.is_none_or(|r| date <= r)
+            }
+            Lifecycle::Never => false,
+        }
+    }
+}
+
+/// Lifecycle of `title`, with the start and retirement dates from the config winning over
+/// the ones seen in the notes. Seen in the notes, a habit starts on its first entry and is
+/// retired on its last one once it has been missing for [`RETIRED_AFTER_DAYS`].
+pub fn lifecycle(hdps: &[HabitDayPerformance], title: &str, config: &Config) -> Lifecycle {
+    let mut seen = hdps
+        .iter()
+        .filter(|hdp| recorded(hdp, title).is_some())
+        .map(|hdp| hdp.date());
+    let first = seen.next();
+    let last = seen.next_back().or(first);
+    let latest = hdps.last().map(|hdp| hdp.date());
+    let declared = config.habits.get(title);
+
+    let start = declared.and_then(|h| h.start).or(first);
+    let retired = declared.and_then(|h| h.retired).or(match (last, latest) {
+        (Some(last), Some(latest)) if (latest - last).num_days() > RETIRED_AFTER_DAYS => Some(last),
+        _ => None,
+    });
+    match (
+        first,
+        declared.is_some_and(|h| h.start.is_some() || h.retired.is_some()),
+    ) {
+        (None, false) => Lifecycle::Never,
+        _ => Lifecycle::Active { start, retired },
+    }
+}
+
+/// Done versus expected counts for one habit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HabitStats {
+    pub title: String,
+    pub done: u32,
+    pub expected: u32,
+}
+
+impl HabitStats {
+    pub fn rate(&self) -> f32 {
+        if self.expected == 0 {
+            return 0.0;
+        }
+        self.done as f32 / self.expected as f32
+    }
+}
+
+impl fmt::Display for HabitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.expected == 0 {
+            return write!(f, "{}: nothing expected", self.title);
+        }
+        write!(
+            f,
+            "{}: {}/{} ({:.0}%)",
+            self.title,
+            self.done,
+            self.expected,
+            self.rate() * 100.0
+        )
+    }
+}
+
+fn recorded(hdp: &HabitDayPerformance, title: &str) -> Option<Performance> {
+    hdp.todo_performance
+        .iter()
+        .find(|t| t.title() == title)
+        .map(|t| t.performance())
+}
+
+fn is_done(hdp: &HabitDayPerformance, title: &str) -> bool {
+    matches!(recorded(hdp, title), Some(Performance::Done))
+}
+
+/// Done count and number of day files per ISO week
+fn weekly_counts(hdps: &[HabitDayPerformance], title: &str) -> HashMap<IsoWeek, (u32, u32)> {
+    let mut weeks: HashMap<IsoWeek, (u32, u32)> = HashMap::new();
+    for hdp in hdps {
+        let entry = weeks.entry(hdp.date().iso_week()).or_default();
+        if is_done(hdp, title) {
+            entry.0 += 1;
+        }
+        entry.1 += 1;
+    }
+    weeks
+}
+
+/// The week of `as_of`, the last recorded day, is still running unless that day is its Sunday
+fn week_in_progress(as_of: NaiveDate, week: IsoWeek) -> bool {
+    as_of.iso_week() == week && as_of.weekday() != Weekday::Sun
+}
+
+/// Last recorded day of `hdps`, for callers that hold the whole history
+pub fn last_day(hdps: &[HabitDayPerformance]) -> NaiveDate {
+    hdps.last().map_or(NaiveDate::MIN, |hdp| hdp.date())
+}
+
+/// Days standing in for the ticks a finished week fell short of a `times` per week target,
+/// FAIL entries first and then the earliest days without an entry
+fn shortfall_days(
+    active: &[HabitDayPerformance],
+    title: &str,
+    times: u32,
+    as_of: NaiveDate,
+) -> HashSet<NaiveDate> {
+    let mut weeks: HashMap<IsoWeek, Vec<&HabitDayPerformance>> = HashMap::new();
+    for hdp in active {
+        weeks.entry(hdp.date().iso_week()).or_default().push(hdp);
+    }
+    weeks
+        .into_iter()
+        .filter(|(week, _)| !week_in_progress(as_of, *week))
+        .flat_map(|(_, days)| {
+            let expected = times.min(days.len() as u32);
+            let done = days.iter().filter(|hdp| is_done(hdp, title)).count() as u32;
+            let mut open: Vec<&HabitDayPerformance> = days
+                .into_iter()
+                .filter(|hdp| !is_done(hdp, title))
+                .collect();
+            open.sort_by_key(|hdp| (recorded(hdp, title).is_none(), hdp.date()));
+            open.into_iter()
+                .take(expected.saturating_sub(done) as usize)
+                .map(|hdp| hdp.date())
+        })
+        .collect()
+}
+
+/// Monday and missing ticks of every finished week that fell short of a `times` per
+/// week target, oldest first. Weeks that met it are listed with no missing ticks.
+pub fn weekly_shortfalls(
+    hdps: &[HabitDayPerformance],
+    title: &str,
+    times: u32,
+    lifecycle: Lifecycle,
+    as_of: NaiveDate,
+) -> Vec<(NaiveDate, u32)> {
+    let mut weeks: BTreeMap<NaiveDate, (u32, u32)> = BTreeMap::new();
+    for hdp in active_days(hdps, lifecycle) {
+        let date = hdp.date();
+        let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+        let entry = weeks.entry(monday).or_default();
+        entry.0 += is_done(&hdp, title) as u32;
+        entry.1 += 1;
+    }
+    weeks
+        .into_iter()
+        .filter(|(monday, _)| !week_in_progress(as_of, monday.iso_week()))
+        .map(|(monday, (done, days))| (monday, times.min(days).saturating_sub(done)))
+        .collect()
+}
+
+/// Days of `hdps` inside `lifecycle`
+fn active_days(hdps: &[HabitDayPerformance], lifecycle: Lifecycle) -> Vec<HabitDayPerformance> {
+    hdps.iter()
+        .filter(|hdp| lifecycle.is_active(hdp.date()))
+        .cloned()
+        .collect()
+}
+
+/// Status of `title` for every day in `hdps`. A per week habit is only due on as many days
+/// as its finished weeks fell short, matching what [`habit_stats`] expects. `as_of` is the
+/// last recorded day of the whole history, whatever slice `hdps` is.
+pub fn habit_statuses(
+    hdps: &[HabitDayPerformance],
+    title: &str,
+    schedule: &Schedule,
+    lifecycle: Lifecycle,
+    as_of: NaiveDate,
+) -> BTreeMap<NaiveDate, HabitStatus> {
+    let shortfall = match schedule {
+        Schedule::PerWeek { times } => {
+            shortfall_days(&active_days(hdps, lifecycle), title, *times, as_of)
+        }
+        _ => HashSet::new(),
+    };
+
+    hdps.iter()
+        .map(|hdp| {
+            let date = hdp.date();
+            if !lifecycle.is_active(date) {
+                return (date, HabitStatus::Inactive);
+            }
+            let due = match schedule {
+                Schedule::PerWeek { .. } => shortfall.contains(&date),
+                _ => schedule.is_due(date),
+            };
+            let status = match (recorded(hdp, title), due) {
+                (Some(Performance::Done), _) => HabitStatus::Done,
+                (_, false) => HabitStatus::NotScheduled,
+                (Some(_), true) => HabitStatus::Missed,
+                (None, true) => HabitStatus::Unrecorded,
+            };
+            (date, status)
+        })
+        .collect()
+}
+
+/// Completion of `title` over `hdps`, only counting the active days its schedule asks for.
+/// A per week habit is expected `times` times in each week (fewer if the notes cover fewer days),
+/// and a week that is still running on `as_of`, the last recorded day of the whole history,
+/// can't count against it yet.
+pub fn habit_stats(
+    hdps: &[HabitDayPerformance],
+    title: &str,
+    schedule: &Schedule,
+    lifecycle: Lifecycle,
+    as_of: NaiveDate,
+) -> HabitStats {
+    let active = &active_days(hdps, lifecycle);
+    let (done, expected) = match schedule {
+        Schedule::PerWeek { times } => weekly_counts(active, title).into_iter().fold(
+            (0, 0),
+            |(done, expected), (week, (week_done, week_days))| {
+                let mut week_expected = (*times).min(week_days);
+                if week_in_progress(as_of, week) {
+                    week_expected = week_expected.min(week_done);
+                }
+                (
+                    done + week_done.min(week_expected),
+                    expected + week_expected,
+                )
+            },
+        ),
+        _ => active
+            .iter()
+            .filter(|hdp| schedule.is_due(hdp.date()))
+            .fold((0, 0), |(done, expected), hdp| {
+                (done + is_done(hdp, title) as u32, expected + 1)
+            }),
+    };
+
+    HabitStats {
+        title: title.to_string(),
+        done,
+        expected,
+    }
+}
+
+/// Statuses for each of `titles` using the schedules from `config`, lifecycles are worked out
+/// from `hdps` so it should be the whole history
+pub fn status_grid(hdps: &[HabitDayPerformance], titles: &[&str], config: &Config) -> StatusGrid {
+    titles
+        .iter()
+        .map(|title| {
+            let schedule = config.schedule(title);
+            let lifecycle = lifecycle(hdps, title, config);
+            (
+                title.to_string(),
+                habit_statuses(hdps, title, &schedule, lifecycle, last_day(hdps)),
+            )
+        })
+        .collect()
+}
+
+/// Rolls the statuses of every `#N` instance of a habit on one day into a single one
+fn combine_instances(statuses: &[HabitStatus]) -> HabitStatus {
+    let expected = statuses
+        .iter()
+        .filter(|s| !matches!(s, HabitStatus::NotScheduled | HabitStatus::Inactive))
+        .count() as u32;
+    let done = statuses.iter().filter(|s| **s == HabitStatus::Done).count() as u32;
+    let recorded = statuses
+        .iter()
+        .any(|s| matches!(s, HabitStatus::Done | HabitStatus::Missed));
+
+    if statuses.iter().all(|s| *s == HabitStatus::Inactive) {
+        HabitStatus::Inactive
+    } else if expected == 0 {
+        HabitStatus::NotScheduled
+    } else if done == expected {
+        HabitStatus::Done
+    } else if done > 0 {
+        HabitStatus::Partial(done, expected)
+    } else if recorded {
+        HabitStatus::Missed
+    } else {
+        HabitStatus::Unrecorded
+    }
+}
+
+/// Statuses per habit name, each day combining all the numbered instances of `titles` that share it
+pub fn group_grid(
+    hdps: &[HabitDayPerformance],
+    names: &[&str],
+    titles: &[&str],
+    config: &Config,
+) -> StatusGrid {
+    let instances = status_grid(hdps, titles, config);
+
+    names
+        .iter()
+        .map(|name| {
+            let members: Vec<&BTreeMap<NaiveDate, HabitStatus>> = titles
+                .iter()
+                .filter(|title| habit_instance(title).0 == *name)
+                .map(|title| &instances[*title])
+                .collect();
+            let days = hdps
+                .iter()
+                .map(|hdp| {
+                    let date = hdp.date();
+                    let statuses: Vec<HabitStatus> =
+                        members.iter().map(|days| days[&date]).collect();
+                    (date, combine_instances(&statuses))
+                })
+                .collect();
+            (name.to_string(), days)
+        })
+        .collect()
+}
+
+/// How much of a multi instance habit gets done on an average day
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupStats {
+    pub name: String,
+    pub instances: usize,
+    pub days: u32,
+    pub fraction_sum: f32,
+}
+
+impl GroupStats {
+    pub fn daily_fraction(&self) -> f32 {
+        if self.days == 0 {
+            return 0.0;
+        }
+        self.fraction_sum / self.days as f32
+    }
+}
+
+impl fmt::Display for GroupStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}x): {:.0}% a day over {} days",
+            self.name,
+            self.instances,
+            self.daily_fraction() * 100.0,
+            self.days
+        )
+    }
+}
+
+/// Daily done fraction for every habit name, skipping days where none of its instances were due
+pub fn group_stats(grid: &StatusGrid, names: &[&str], titles: &[&str]) -> Vec<GroupStats> {
+    names
+        .iter()
+        .map(|name| {
+            let instances = titles
+                .iter()
+                .filter(|title| habit_instance(title).0 == *name)
+                .count();
+            let mut stats = GroupStats {
+                name: name.to_string(),
+                instances,
+                days: 0,
+                fraction_sum: 0.0,
+            };
+            for status in grid[*name].values() {
+                let fraction = match status {
+                    HabitStatus::Done => 1.0,
+                    HabitStatus::Partial(done, expected) => *done as f32 / *expected as f32,
+                    HabitStatus::Missed | HabitStatus::Unrecorded => 0.0,
+                    HabitStatus::NotScheduled | HabitStatus::Inactive => continue,
+                };
+                stats.days += 1;
+                stats.fraction_sum += fraction;
+            }
+            stats
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_stuff::{Task, TaskPerformance};
+    use chrono::Days;
+
+    const ALWAYS: Lifecycle = Lifecycle::Active {
+        start: None,
+        retired: None,
+    };
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    /// Consecutive days from `start`, with `Swim` recorded as `days` says
+    fn swims(start: &str, days: &[Option<Performance>]) -> Vec<HabitDayPerformance> {
+        days.iter()
+            .enumerate()
+            .map(|(i, performance)| {
+                let mut hdp = HabitDayPerformance::new(date(start) + Days::new(i as u64));
+                if let Some(performance) = performance {
+                    let task = Task::Todo("Swim".to_string());
+                    hdp.todo_performance
+                        .push(TaskPerformance::new(task, performance.clone()));
+                }
+                hdp
+            })
+            .collect()
+    }
+
+    #[test]
+    fn schedules_come_from_the_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [habits."Sauna"]
+            schedule = { kind = "weekdays", days = ["Sat", "Sun"] }
+
+            [habits."Anki"]
+            schedule = { kind = "every", days = 2, from = "2025-03-01" }
+            "#,
+        )
+        .unwrap();
+        let sauna = config.schedule("Sauna");
+        assert!(sauna.is_due(date("2025-03-08")));
+        assert!(!sauna.is_due(date("2025-03-07")));
+        let anki = config.schedule("Anki");
+        assert!(anki.is_due(date("2025-03-03")));
+        assert!(!anki.is_due(date("2025-03-04")));
+        assert!(!anki.is_due(date("2025-02-27")));
+        assert!(config.schedule("Walk").is_due(date("2025-03-04")));
+    }
+
+    #[test]
+    fn weekly_targets_only_miss_the_shortfall() {
+        use Performance::{Done, Fail};
+        let swim = Schedule::PerWeek { times: 2 };
+        // Monday 2025-03-03 to Sunday 2025-03-09, then a Monday of the next week
+        let mut hdps = swims(
+            "2025-03-03",
+            &[None, Some(Done), None, Some(Fail), None, None, None],
+        );
+        let stats = habit_stats(&hdps, "Swim", &swim, ALWAYS, last_day(&hdps));
+        assert_eq!((stats.done, stats.expected), (1, 2));
+        let statuses = habit_statuses(&hdps, "Swim", &swim, ALWAYS, last_day(&hdps));
+        assert_eq!(statuses[&date("2025-03-04")], HabitStatus::Done);
+        assert_eq!(statuses[&date("2025-03-06")], HabitStatus::Missed);
+        assert_eq!(statuses[&date("2025-03-03")], HabitStatus::NotScheduled);
+
+        // A week still running can't count against the habit yet
+        hdps.extend(swims("2025-03-10", &[None]));
+        let running = habit_stats(&hdps, "Swim", &swim, ALWAYS, last_day(&hdps));
+        assert_eq!((running.done, running.expected), (1, 2));
+        let statuses = habit_statuses(&hdps, "Swim", &swim, ALWAYS, last_day(&hdps));
+        assert_eq!(statuses[&date("2025-03-10")], HabitStatus::NotScheduled);
+    }
+
+    #[test]
+    fn day_schedules_only_count_due_days() {
+        use Performance::Done;
+        let sauna = Schedule::Weekdays {
+            days: vec![Weekday::Sat, Weekday::Sun],
+        };
+        // Friday 2025-03-07 to Sunday 2025-03-09
+        let hdps = swims("2025-03-07", &[Some(Done), None, Some(Done)]);
+        let stats = habit_stats(&hdps, "Swim", &sauna, ALWAYS, last_day(&hdps));
+        assert_eq!(stats.to_string(), "Swim: 1/2 (50%)");
+        let statuses = habit_statuses(&hdps, "Swim", &sauna, ALWAYS, last_day(&hdps));
+        assert_eq!(statuses[&date("2025-03-08")], HabitStatus::Unrecorded);
+    }
+
+    #[test]
+    fn instances_combine_into_partial_days() {
+        use HabitStatus::*;
+        assert_eq!(combine_instances(&[Done, Missed]), Partial(1, 2));
+        assert_eq!(combine_instances(&[Done, NotScheduled]), Done);
+        assert_eq!(combine_instances(&[Missed, Unrecorded]), Missed);
+        assert_eq!(combine_instances(&[Unrecorded, Unrecorded]), Unrecorded);
+        assert_eq!(combine_instances(&[NotScheduled]), NotScheduled);
+    }
+
+    #[test]
+    fn groups_average_their_daily_fraction() {
+        use Performance::{Done, Fail};
+        let titles = ["#1 Walk", "#2 Walk"];
+        let days = [(Done, Fail), (Done, Done), (Fail, Fail)];
+        let hdps: Vec<HabitDayPerformance> = days
+            .iter()
+            .enumerate()
+            .map(|(i, (first, second))| {
+                let mut hdp = HabitDayPerformance::new(date("2025-03-03") + Days::new(i as u64));
+                for (title, performance) in titles.iter().zip([first, second]) {
+                    let task = Task::Todo(title.to_string());
+                    hdp.todo_performance
+                        .push(TaskPerformance::new(task, performance.clone()));
+                }
+                hdp
+            })
+            .collect();
+        let grid = group_grid(&hdps, &["Walk"], &titles, &Config::default());
+        assert_eq!(
+            grid["Walk"][&date("2025-03-03")],
+            HabitStatus::Partial(1, 2)
+        );
+        let stats = group_stats(&grid, &["Walk"], &titles);
+        assert_eq!(stats[0].to_string(), "Walk (2x): 50% a day over 3 days");
+    }
+}
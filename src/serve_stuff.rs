@@ -0,0 +1,548 @@
+//! HTTP API serving habit data and rendered charts
+
+use chrono::NaiveDate;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::analyze_stuff::{
+    Analyzer, Overlay, MAX_RANGE_DAYS, OBJECTIVE_GRID_SIZE, TIME_OF_DAY_SIZE, WEEKDAY_SIZE,
+};
+use crate::config_stuff::Config;
+use crate::data_stuff::{HabitDayPerformance, Performance};
+use crate::query_stuff::DayFilter;
+use crate::time_stuff::{DateSpan, TimeFrame};
+
+/// Newest modification time and file count under the notes, changes whenever a day is
+/// added, edited or removed
+#[derive(PartialEq, Debug)]
+struct NotesStamp {
+    newest: Option<SystemTime>,
+    files: usize,
+}
+
+/// How long a notes stamp is trusted before the tree is walked again, so a page pulling in
+/// a dozen charts walks it once
+const STAMP_TTL: Duration = Duration::from_secs(2);
+
+fn notes_stamp(dir: &Path) -> NotesStamp {
+    let mut stamp = NotesStamp {
+        newest: None,
+        files: 0,
+    };
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let meta = match entry.metadata() {
+                Ok(meta) => meta,
+                Err(_) => continue,
+            };
+            if meta.is_dir() {
+                dirs.push(entry.path());
+                continue;
+            }
+            stamp.files += 1;
+            if let Ok(modified) = meta.modified() {
+                stamp.newest = stamp.newest.max(Some(modified));
+            }
+        }
+    }
+    stamp
+}
+
+/// Status, content type and body answering one request
+pub struct Reply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    fn json(value: Value) -> Self {
+        Reply {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Reply {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message }).to_string().into_bytes(),
+        }
+    }
+}
+
+enum Chart<'a> {
+    Habits {
+        frame: TimeFrame<DateSpan>,
+        grouped: bool,
+    },
+    Objective {
+        frame: TimeFrame<DateSpan>,
+        key: &'a str,
+    },
+    ObjectiveGrid {
+        frame: TimeFrame<DateSpan>,
+    },
+    Overlay {
+        frame: TimeFrame<DateSpan>,
+        overlay: Overlay,
+    },
+    TimeOfDay {
+        frame: TimeFrame<DateSpan>,
+    },
+    Weekdays {
+        frame: TimeFrame<DateSpan>,
+    },
+    ObjectiveWeekdays {
+        frame: TimeFrame<DateSpan>,
+        key: &'a str,
+    },
+}
+
+impl Chart<'_> {
+    fn size(&self, a: &Analyzer) -> (u32, u32) {
+        match self {
+            Chart::Habits { frame, grouped } => a.heatmap_size(frame, *grouped),
+            Chart::Objective { .. } | Chart::Overlay { .. } => a.theme().objective_size,
+            Chart::ObjectiveGrid { .. } => OBJECTIVE_GRID_SIZE,
+            Chart::TimeOfDay { .. } => TIME_OF_DAY_SIZE,
+            Chart::Weekdays { .. } | Chart::ObjectiveWeekdays { .. } => WEEKDAY_SIZE,
+        }
+    }
+
+    fn draw<DB: DrawingBackend>(
+        &self,
+        a: &Analyzer,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), String> {
+        match self {
+            Chart::Habits { frame, grouped } => {
+                a.draw_habits_on(root, frame, *grouped);
+                Ok(())
+            }
+            Chart::Objective { frame, key } => a.draw_objective_on(root, frame, key),
+            Chart::ObjectiveGrid { frame } => {
+                a.draw_objective_grid_on(root, frame);
+                Ok(())
+            }
+            Chart::Overlay { frame, overlay } => a.draw_overlay_on(root, frame, overlay),
+            Chart::TimeOfDay { frame } => {
+                a.draw_time_of_day_on(root, frame);
+                Ok(())
+            }
+            Chart::Weekdays { frame } => {
+                a.draw_weekdays_on(root, frame);
+                Ok(())
+            }
+            Chart::ObjectiveWeekdays { frame, key } => {
+                a.draw_objective_weekdays_on(root, frame, key)
+            }
+        }
+    }
+
+    /// Renders the chart as `png` or `svg`
+    fn render(&self, a: &Analyzer, format: &str) -> Result<Reply, String> {
+        let (width, height) = self.size(a);
+        match format {
+            "svg" => {
+                let mut svg = String::new();
+                {
+                    let root =
+                        SVGBackend::with_string(&mut svg, (width, height)).into_drawing_area();
+                    self.draw(a, &root)?;
+                }
+                Ok(Reply {
+                    status: 200,
+                    content_type: "image/svg+xml",
+                    body: svg.into_bytes(),
+                })
+            }
+            "png" => {
+                let mut buffer = vec![0; width as usize * height as usize * 3];
+                {
+                    let root = BitMapBackend::with_buffer(&mut buffer, (width, height))
+                        .into_drawing_area();
+                    self.draw(a, &root)?;
+                }
+                let image = image::RgbImage::from_raw(width, height, buffer)
+                    .ok_or("chart buffer has the wrong size".to_string())?;
+                let mut png = Cursor::new(vec![]);
+                image
+                    .write_to(&mut png, image::ImageOutputFormat::Png)
+                    .map_err(|e| e.to_string())?;
+                Ok(Reply {
+                    status: 200,
+                    content_type: "image/png",
+                    body: png.into_inner(),
+                })
+            }
+            _ => Err(format!(
+                "unknown chart format {format}, expected png or svg"
+            )),
+        }
+    }
+}
+
+/// Undoes %XX escapes so titles like `#1 Walk` can be used in paths
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut decoded = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => raw
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// f32 as it reads in the notes, 82.4 rather than 82.40000152587891
+fn number(value: f32) -> Value {
+    json!(value.to_string().parse::<f64>().unwrap_or_default())
+}
+
+/// A day's habits and objective values, with every timed or repeated reading listed under
+/// `readings`
+fn day_json(hdp: &HabitDayPerformance, config: &Config) -> Value {
+    let mut habits = Map::new();
+    for tp in &hdp.todo_performance {
+        let status = match tp.performance() {
+            Performance::Done => "done",
+            Performance::Fail => "fail",
+            Performance::Score(_) | Performance::Measured(_) => continue,
+        };
+        habits.insert(tp.title().to_string(), json!(status));
+    }
+    let mut objectives = Map::new();
+    let mut readings: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for tp in &hdp.objective_performance {
+        let title = tp.title();
+        if let Some(value) = hdp.objective_by(title, config.aggregate(title)) {
+            objectives.entry(title.to_string()).or_insert(number(value));
+        }
+        let value = match tp.performance() {
+            Performance::Score(value) => value,
+            Performance::Measured(quantity) => quantity.value,
+            _ => continue,
+        };
+        let reading = json!({
+            "value": number(value),
+            "time": tp.time().map(|t| t.format("%H:%M").to_string()),
+        });
+        readings.entry(title).or_default().push(reading);
+    }
+    json!({
+        "date": hdp.date(),
+        "habits": habits,
+        "objectives": objectives,
+        "readings": readings,
+        "tags": hdp.tags,
+        "notes": hdp.notes,
+    })
+}
+
+/// Splits `{label}.{format}` from the end of a chart path
+fn chart_file(file: &str) -> Result<(&str, &str), String> {
+    match file.rsplit_once('.') {
+        Some((label, format @ ("png" | "svg"))) => Ok((label, format)),
+        Some((_, format)) => Err(format!(
+            "unknown chart format {format}, expected png or svg"
+        )),
+        None => Err(format!("{file} needs a .png or .svg extension")),
+    }
+}
+
+fn chart_frame(a: &Analyzer, label: &str) -> Result<TimeFrame<DateSpan>, String> {
+    a.frame_by_label(label).ok_or(format!(
+        "no time frame labelled {label}, ranges run from an earlier to a later day over at \
+         most {MAX_RANGE_DAYS} days"
+    ))
+}
+
+/// Decoded value of `name` in a query string like `only=travel,!sick&theme=dark`
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| percent_decode(&value.replace('+', " ")))
+}
+
+/// Narrows `a` down to the days in `only` and draws with the palette in `theme`, both
+/// falling back to the config when left out
+pub fn apply_query(a: &mut Analyzer, query: &str) -> Result<(), String> {
+    let filter = match query_param(query, "only") {
+        Some(only) => only.parse()?,
+        None => DayFilter::default(),
+    };
+    let mut theme = a.config().theme.clone();
+    if let Some(palette) = query_param(query, "theme") {
+        theme.palette = palette.parse()?;
+    }
+    a.set_filter(filter);
+    a.set_theme(theme);
+    Ok(())
+}
+
+/// Answers a GET of `path`, with any query already applied to `a`
+pub fn route(a: &Analyzer, path: &str) -> Reply {
+    let segments: Vec<String> = path
+        .trim_matches('/')
+        .split('/')
+        .map(percent_decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+    let hdps = a.progress().hpds();
+    if let ["charts", .., file] = segments.as_slice() {
+        if let Err(e) = chart_file(file) {
+            return Reply::error(400, &e);
+        }
+    }
+
+    let chart = match segments.as_slice() {
+        ["days"] => {
+            let days = a.days();
+            return Reply::json(days.iter().map(|hdp| day_json(hdp, a.config())).collect());
+        }
+        ["days", date] => {
+            let date: NaiveDate = match date.parse() {
+                Ok(date) => date,
+                Err(_) => return Reply::error(400, &format!("{date} is not a YYYY-MM-DD date")),
+            };
+            return match a.days().iter().find(|hdp| hdp.date() == date) {
+                Some(hdp) => Reply::json(day_json(hdp, a.config())),
+                None => Reply::error(404, &format!("no notes for {date}")),
+            };
+        }
+        ["habits", title, "stats"] => {
+            let title = a.config().canonical_title(title);
+            let title = title.as_str();
+            let known = a.config().known_habits().contains(&title)
+                || hdps
+                    .iter()
+                    .any(|hdp| hdp.todo_performance.iter().any(|t| t.title() == title));
+            if !known {
+                return Reply::error(404, &format!("no habit called {title}"));
+            }
+            let stats = a.habit_stats(title);
+            let times = a.habit_times(title);
+            return Reply::json(json!({
+                "title": stats.title,
+                "done": stats.done,
+                "expected": stats.expected,
+                "rate": number(stats.rate()),
+                "typical_time": times.typical().map(|t| t.format("%H:%M").to_string()),
+                "clocked_minutes": times.clocked,
+            }));
+        }
+        ["objectives", key, "series"] => {
+            return match a.objective_series(key) {
+                Ok(series) => Reply::json(
+                    series
+                        .into_iter()
+                        .map(|(date, value)| json!({ "date": date, "value": number(value) }))
+                        .collect(),
+                ),
+                Err(e) => Reply::error(404, &e),
+            };
+        }
+        ["charts", kind @ ("habits" | "habit-groups"), file] => {
+            chart_file(file).and_then(|(label, format)| {
+                let chart = Chart::Habits {
+                    frame: chart_frame(a, label)?,
+                    grouped: *kind == "habit-groups",
+                };
+                Ok((chart, format))
+            })
+        }
+        ["charts", "time-of-day", file] => chart_file(file).and_then(|(label, format)| {
+            let chart = Chart::TimeOfDay {
+                frame: chart_frame(a, label)?,
+            };
+            Ok((chart, format))
+        }),
+        ["charts", "weekdays", file] => chart_file(file).and_then(|(label, format)| {
+            let chart = Chart::Weekdays {
+                frame: chart_frame(a, label)?,
+            };
+            Ok((chart, format))
+        }),
+        ["charts", "weekdays", key, file] => chart_file(file).and_then(|(label, format)| {
+            let chart = Chart::ObjectiveWeekdays {
+                frame: chart_frame(a, label)?,
+                key,
+            };
+            Ok((chart, format))
+        }),
+        ["charts", "objectives", file] => chart_file(file).and_then(|(label, format)| {
+            let chart = Chart::ObjectiveGrid {
+                frame: chart_frame(a, label)?,
+            };
+            Ok((chart, format))
+        }),
+        ["charts", "overlay", left, right, file] => chart_file(file).and_then(|(label, format)| {
+            let chart = Chart::Overlay {
+                frame: chart_frame(a, label)?,
+                overlay: format!("{left}:{right}").parse()?,
+            };
+            Ok((chart, format))
+        }),
+        ["charts", "objectives", key, file] => chart_file(file).and_then(|(label, format)| {
+            let chart = Chart::Objective {
+                frame: chart_frame(a, label)?,
+                key,
+            };
+            Ok((chart, format))
+        }),
+        _ => return Reply::error(404, &format!("nothing at {path}")),
+    };
+
+    match chart.and_then(|(chart, format)| chart.render(a, format)) {
+        Ok(reply) => reply,
+        Err(e) => Reply::error(404, &e),
+    }
+}
+
+/// Answers API and chart requests on `address` until the process is stopped. `load` is
+/// called at start and again when something under `notes_dir` has changed, which is looked
+/// at no more than once every [`STAMP_TTL`].
+pub fn serve(
+    address: &str,
+    notes_dir: &Path,
+    load: impl Fn() -> Result<Analyzer, String>,
+) -> Result<(), String> {
+    let server = Server::http(address).map_err(|e| format!("{address}: {e}"))?;
+    let mut stamp = notes_stamp(notes_dir);
+    let mut stamped = Instant::now();
+    let mut analyzer = load()?;
+    println!("serving {} on http://{address}", notes_dir.display());
+
+    for request in server.incoming_requests() {
+        if stamped.elapsed() >= STAMP_TTL {
+            let current = notes_stamp(notes_dir);
+            stamped = Instant::now();
+            if current != stamp {
+                match load() {
+                    Ok(reloaded) => {
+                        analyzer = reloaded;
+                        println!("reloaded {}", notes_dir.display());
+                    }
+                    Err(e) => println!("reload failed, keeping the old data: {e}"),
+                }
+                stamp = current;
+            }
+        }
+
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let reply = match (request.method(), apply_query(&mut analyzer, query)) {
+            (Method::Get, Ok(())) => route(&analyzer, path),
+            (Method::Get, Err(e)) => Reply::error(400, &e),
+            _ => Reply::error(405, "only GET is supported"),
+        };
+        println!("{} {url} {}", request.method(), reply.status);
+
+        let header = Header::from_bytes("Content-Type", reply.content_type).unwrap();
+        let response = Response::from_data(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header);
+        if let Err(e) = request.respond(response) {
+            println!("answering {url} failed: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_stuff::{HabitProgress, Task, TaskPerformance};
+
+    fn analyzer() -> Analyzer {
+        let hdps = ["2025-03-01", "2025-03-02"]
+            .iter()
+            .zip([Performance::Done, Performance::Fail])
+            .map(|(date, walk)| {
+                let mut hdp = HabitDayPerformance::new(date.parse().unwrap());
+                let task = Task::Todo("#1 Walk".to_string());
+                hdp.todo_performance.push(TaskPerformance::new(task, walk));
+                let task = Task::Objective("Weight=".to_string());
+                hdp.objective_performance
+                    .push(TaskPerformance::new(task, Performance::Score(82.4)));
+                hdp
+            })
+            .collect();
+        Analyzer::from_progress(HabitProgress(hdps))
+    }
+
+    fn body(reply: &Reply) -> Value {
+        serde_json::from_slice(&reply.body).unwrap()
+    }
+
+    #[test]
+    fn days_and_stats_are_json() {
+        let a = analyzer();
+        let day = route(&a, "/days/2025-03-01");
+        assert_eq!(day.status, 200);
+        assert_eq!(
+            body(&day),
+            json!({
+                "date": "2025-03-01",
+                "habits": { "#1 Walk": "done" },
+                "objectives": { "Weight=": 82.4 },
+                "readings": { "Weight=": [{ "value": 82.4, "time": null }] },
+                "tags": [],
+                "notes": [],
+            })
+        );
+        assert_eq!(route(&a, "/days/2025-03-09").status, 404);
+        assert_eq!(route(&a, "/days/March").status, 400);
+
+        let stats = route(&a, "/habits/%231%20Walk/stats");
+        assert_eq!(body(&stats)["done"], json!(1));
+        assert_eq!(body(&stats)["expected"], json!(2));
+        assert_eq!(route(&a, "/habits/Juggle/stats").status, 404);
+    }
+
+    #[test]
+    fn chart_paths_are_checked_before_rendering() {
+        let a = analyzer();
+        assert_eq!(route(&a, "/charts/habits/2025-March.gif").status, 400);
+        assert_eq!(route(&a, "/charts/habits/2025-March").status, 400);
+        assert_eq!(route(&a, "/charts/habits/2024-March.svg").status, 404);
+        assert_eq!(
+            route(&a, "/charts/habits/2025-03-02_2025-03-01.svg").status,
+            404
+        );
+        let chart = route(&a, "/charts/objectives/weight/2025-March.svg");
+        assert_eq!((chart.status, chart.content_type), (200, "image/svg+xml"));
+        assert_eq!(route(&a, "/nowhere").status, 404);
+    }
+}
@@ -0,0 +1,152 @@
+//! Where habit days are loaded from
+
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::data_stuff::{collect_org_data, day_file_path, HabitDayPerformance, HabitProgress};
+
+pub const DEFAULT_NOTES_DIR: &str = "/home/test/code/notes/habits";
+
+/// Anything that can hand the analyzer a set of habit days
+pub trait HabitSource {
+    /// Every day the source knows about, oldest first
+    fn load_all(&self) -> HabitProgress;
+
+    /// Days between `start` and `end` inclusive, oldest first
+    fn load_range(&self, start: NaiveDate, end: NaiveDate) -> HabitProgress;
+
+    /// Dates that have data, oldest first
+    fn available_dates(&self) -> Vec<NaiveDate>;
+}
+
+/// Reads day files laid out as `{root}/{year}/{Month}/{DD}.org`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrgDirSource {
+    pub root: PathBuf,
+    pub start: NaiveDate,
+}
+
+impl OrgDirSource {
+    pub fn new(root: PathBuf, start: NaiveDate) -> Self {
+        OrgDirSource { root, start }
+    }
+}
+
+impl Default for OrgDirSource {
+    fn default() -> Self {
+        let start = NaiveDate::from_ymd_opt(2025, 2, 22).unwrap();
+        OrgDirSource::new(PathBuf::from(DEFAULT_NOTES_DIR), start)
+    }
+}
+
+impl HabitSource for OrgDirSource {
+    fn load_all(&self) -> HabitProgress {
+        let today = Local::now().date_naive();
+        collect_org_data(&self.root, self.start, today)
+    }
+
+    fn load_range(&self, start: NaiveDate, end: NaiveDate) -> HabitProgress {
+        collect_org_data(&self.root, start, end)
+    }
+
+    fn available_dates(&self) -> Vec<NaiveDate> {
+        let today = Local::now().date_naive();
+        self.start
+            .iter_days()
+            .take_while(|d| *d <= today)
+            .filter(|d| day_file_path(&self.root, *d).is_file())
+            .collect()
+    }
+}
+
+/// Holds already parsed days, handy for tests and for data that didn't come from org files
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemorySource {
+    hdps: Vec<HabitDayPerformance>,
+}
+
+impl MemorySource {
+    pub fn new(mut hdps: Vec<HabitDayPerformance>) -> Self {
+        hdps.sort_by_key(|hdp| hdp.date());
+        MemorySource { hdps }
+    }
+}
+
+impl HabitSource for MemorySource {
+    fn load_all(&self) -> HabitProgress {
+        HabitProgress(self.hdps.clone())
+    }
+
+    fn load_range(&self, start: NaiveDate, end: NaiveDate) -> HabitProgress {
+        let hdps = self
+            .hdps
+            .iter()
+            .filter(|hdp| {
+                let date = hdp.date();
+                date >= start && date <= end
+            })
+            .cloned()
+            .collect();
+        HabitProgress(hdps)
+    }
+
+    fn available_dates(&self) -> Vec<NaiveDate> {
+        self.hdps.iter().map(|hdp| hdp.date()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    /// Notes tree in the temp dir with a day file for each of `days`
+    fn notes(name: &str, days: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("org_analyzer_{name}_{}", std::process::id()));
+        for day in days {
+            let path = day_file_path(&root, date(day));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "** DONE /HABIT/ Walk\n").unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn org_dir_source_reads_the_day_files_in_range() {
+        let root = notes("dir_source", &["2025-03-03", "2025-03-05", "2025-04-01"]);
+        let source = OrgDirSource::new(root.clone(), date("2025-03-01"));
+        assert_eq!(
+            source.available_dates(),
+            [date("2025-03-03"), date("2025-03-05"), date("2025-04-01")]
+        );
+        let march = source.load_range(date("2025-03-01"), date("2025-03-31"));
+        let dates: Vec<NaiveDate> = march.hpds().iter().map(|hdp| hdp.date()).collect();
+        assert_eq!(dates, [date("2025-03-03"), date("2025-03-05")]);
+        assert_eq!(source.load_all().hpds().len(), 3);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn memory_source_hands_back_its_days_oldest_first() {
+        let root = notes("memory_source", &["2025-03-03", "2025-03-05"]);
+        let mut hdps = OrgDirSource::new(root.clone(), date("2025-03-01"))
+            .load_all()
+            .0;
+        fs::remove_dir_all(root).unwrap();
+        hdps.reverse();
+
+        let source = MemorySource::new(hdps);
+        assert_eq!(
+            source.available_dates(),
+            [date("2025-03-03"), date("2025-03-05")]
+        );
+        let range = source.load_range(date("2025-03-04"), date("2025-03-05"));
+        assert_eq!(range.hpds().len(), 1);
+        assert_eq!(range.hpds()[0].date(), date("2025-03-05"));
+    }
+}
@@ -0,0 +1,287 @@
+//! Colours, fonts, sizes and labels the charts are drawn with
+
+use plotters::prelude::*;
+use plotters::style::full_palette::{
+    BLUEGREY_200, BLUEGREY_50, GREEN_200, GREY_200, GREY_700, ORANGE_700,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::analyze_stuff::OBJECTIVE_SIZE;
+
+/// An opaque colour written as `#rrggbb`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Colour(pub u8, pub u8, pub u8);
+
+impl From<Colour> for RGBColor {
+    fn from(c: Colour) -> Self {
+        RGBColor(c.0, c.1, c.2)
+    }
+}
+
+/// Black or white, whichever reads better on top of `colour`
+pub fn contrasting(colour: RGBColor) -> RGBColor {
+    let luma = 0.299 * colour.0 as f32 + 0.587 * colour.1 as f32 + 0.114 * colour.2 as f32;
+    match luma > 140.0 {
+        true => BLACK,
+        false => WHITE,
+    }
+}
+
+impl FromStr for Colour {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .ok_or(format!("colour {s} should look like #1b9e77"))?;
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("colour {s} isn't hex"))
+        };
+        Ok(Colour(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl TryFrom<String> for Colour {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Colour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl From<Colour> for String {
+    fn from(c: Colour) -> Self {
+        c.to_string()
+    }
+}
+
+/// Every colour a chart uses
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub background: RGBColor,
+    /// Text, axes and cell borders
+    pub foreground: RGBColor,
+    pub done: RGBColor,
+    pub missed: RGBColor,
+    pub not_scheduled: RGBColor,
+    pub inactive: RGBColor,
+    /// Stripe over inactive cells
+    pub inactive_hatch: RGBColor,
+    /// Objective line and points
+    pub series: RGBColor,
+    /// Line and points of the second objective on overlaid charts
+    pub overlay: RGBColor,
+    pub goal: RGBColor,
+    pub trend: RGBColor,
+    /// Lines and labels of tagged or noted days
+    pub annotation: RGBColor,
+    /// Time of day dots and weekday bars
+    pub marker: RGBColor,
+}
+
+/// The built in palettes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteName {
+    /// Green done and dark grey missed on white
+    #[default]
+    Light,
+    /// Okabe-Ito colours, blue done and orange missed, safe for red-green colour blindness
+    #[serde(rename = "colour-blind", alias = "colour_blind", alias = "color-blind")]
+    ColourBlind,
+    /// Light text on a dark background
+    Dark,
+}
+
+impl PaletteName {
+    pub fn palette(&self) -> Palette {
+        let hex = |s: &str| RGBColor::from(s.parse::<Colour>().unwrap());
+        match self {
+            PaletteName::Light => Palette {
+                background: WHITE,
+                foreground: BLACK,
+                done: GREEN_200,
+                missed: GREY_700,
+                not_scheduled: GREY_200,
+                inactive: BLUEGREY_50,
+                inactive_hatch: BLUEGREY_200,
+                series: RED,
+                overlay: BLUE,
+                goal: GREEN,
+                trend: BLUE,
+                annotation: ORANGE_700,
+                marker: BLUE,
+            },
+            PaletteName::ColourBlind => Palette {
+                background: hex("#ffffff"),
+                foreground: hex("#000000"),
+                done: hex("#0072b2"),
+                missed: hex("#e69f00"),
+                not_scheduled: hex("#eeeeee"),
+                inactive: hex("#f5f5f5"),
+                inactive_hatch: hex("#bbbbbb"),
+                series: hex("#d55e00"),
+                overlay: hex("#0072b2"),
+                goal: hex("#009e73"),
+                trend: hex("#56b4e9"),
+                annotation: hex("#cc79a7"),
+                marker: hex("#0072b2"),
+            },
+            PaletteName::Dark => Palette {
+                background: hex("#1e1e1e"),
+                foreground: hex("#dddddd"),
+                done: hex("#388e3c"),
+                missed: hex("#9e9e9e"),
+                not_scheduled: hex("#2c2c2c"),
+                inactive: hex("#263238"),
+                inactive_hatch: hex("#455a64"),
+                series: hex("#ef5350"),
+                overlay: hex("#42a5f5"),
+                goal: hex("#66bb6a"),
+                trend: hex("#42a5f5"),
+                annotation: hex("#ffa726"),
+                marker: hex("#42a5f5"),
+            },
+        }
+    }
+}
+
+impl FromStr for PaletteName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(PaletteName::Light),
+            "colour-blind" | "colour_blind" | "color-blind" => Ok(PaletteName::ColourBlind),
+            "dark" => Ok(PaletteName::Dark),
+            _ => Err(format!(
+                "unknown theme {s}, expected light, colour-blind or dark"
+            )),
+        }
+    }
+}
+
+/// Colours replacing single colours of the chosen palette
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaletteOverrides {
+    pub background: Option<Colour>,
+    pub foreground: Option<Colour>,
+    pub done: Option<Colour>,
+    pub missed: Option<Colour>,
+    pub not_scheduled: Option<Colour>,
+    pub inactive: Option<Colour>,
+    pub inactive_hatch: Option<Colour>,
+    pub series: Option<Colour>,
+    pub overlay: Option<Colour>,
+    pub goal: Option<Colour>,
+    pub trend: Option<Colour>,
+    pub annotation: Option<Colour>,
+    pub marker: Option<Colour>,
+}
+
+/// How the charts look, the `[theme]` table of the config
+///
+/// ```toml
+/// [theme]
+/// palette = "colour-blind"
+/// font = "DejaVu Sans"
+/// cell_size = [80, 16]
+/// label_length = 12
+/// rotate_labels = true
+/// cell_borders = false
+/// colours = { done = "#1b9e77" }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub palette: PaletteName,
+    pub colours: PaletteOverrides,
+    /// Font family every chart text is drawn in
+    pub font: String,
+    /// Pixel size of habit heatmaps, worked out from the number of habits and days when left
+    /// out
+    pub heatmap_size: Option<(u32, u32)>,
+    /// Pixel width and height of a heatmap cell when the heatmap size is worked out
+    pub cell_size: (u32, u32),
+    /// Pixel size of objective line charts
+    pub objective_size: (u32, u32),
+    /// Characters of a heatmap column title kept before it is cut short, 0 keeps them whole
+    pub label_length: usize,
+    /// Writes heatmap column titles bottom to top so longer ones fit
+    pub rotate_labels: bool,
+    /// Breaks heatmap column titles over several lines to fit their column, unless rotated
+    pub wrap_labels: bool,
+    /// Lines between heatmap cells
+    pub cell_borders: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            palette: PaletteName::Light,
+            colours: PaletteOverrides::default(),
+            font: "sans-serif".to_string(),
+            heatmap_size: None,
+            cell_size: (64, 20),
+            objective_size: OBJECTIVE_SIZE,
+            label_length: 0,
+            rotate_labels: false,
+            wrap_labels: true,
+            cell_borders: true,
+        }
+    }
+}
+
+impl Theme {
+    /// The named palette with the overridden colours swapped in
+    pub fn colours(&self) -> Palette {
+        let mut p = self.palette.palette();
+        let o = &self.colours;
+        let pick = |over: Option<Colour>, base: &mut RGBColor| {
+            if let Some(over) = over {
+                *base = over.into();
+            }
+        };
+        pick(o.background, &mut p.background);
+        pick(o.foreground, &mut p.foreground);
+        pick(o.done, &mut p.done);
+        pick(o.missed, &mut p.missed);
+        pick(o.not_scheduled, &mut p.not_scheduled);
+        pick(o.inactive, &mut p.inactive);
+        pick(o.inactive_hatch, &mut p.inactive_hatch);
+        pick(o.series, &mut p.series);
+        pick(o.overlay, &mut p.overlay);
+        pick(o.goal, &mut p.goal);
+        pick(o.trend, &mut p.trend);
+        pick(o.annotation, &mut p.annotation);
+        pick(o.marker, &mut p.marker);
+        p
+    }
+
+    /// `size` points of the theme font in the foreground colour
+    pub fn text(&self, size: u32) -> TextStyle<'_> {
+        let mut style = TextStyle::from((self.font.as_str(), size));
+        style.color = self.colours().foreground.to_backend_color();
+        style
+    }
+
+    /// `title` cut down to [`Theme::label_length`] characters
+    pub fn label(&self, title: &str) -> String {
+        match self.label_length {
+            0 => title.to_string(),
+            n if title.chars().count() > n => title.chars().take(n).collect::<String>() + "...",
+            _ => title.to_string(),
+        }
+    }
+}
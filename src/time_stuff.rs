@@ -1,48 +1,272 @@
-use chrono::{Datelike, Local, NaiveDate};
+//! Date spans and the calendar time frames reports are cut into
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-use crate::data_stuff::HabitDayPerformance;
-
-#[derive(Debug)]
-pub struct CurrentDate {
-    pub year: String,  // YYYY
-    pub month: String, // Full month name, first letter capitalized
-    pub day: String,   // DD
-}
-
-impl CurrentDate {
-    /// Creates a new FilePath with the current date
-    pub fn new() -> Self {
-        let now = Local::now();
-        let year = now.year().to_string();
-
-        // Get full month name with first letter capitalized
-        let month = match now.month() {
-            1 => "January",
-            2 => "February",
-            3 => "March",
-            4 => "April",
-            5 => "May",
-            6 => "June",
-            7 => "July",
-            8 => "August",
-            9 => "September",
-            10 => "October",
-            11 => "November",
-            12 => "December",
-            _ => unreachable!(),
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TimeFrame<T> {
+    Week(T),
+    Month(T),
+    Quarter(T),
+    Year(T),
+    Range(T),
+}
+
+impl<T> TimeFrame<T> {
+    pub fn inner(&self) -> &T {
+        match self {
+            TimeFrame::Week(t)
+            | TimeFrame::Month(t)
+            | TimeFrame::Quarter(t)
+            | TimeFrame::Year(t)
+            | TimeFrame::Range(t) => t,
+        }
+    }
+
+    /// Same kind of time frame holding something else
+    pub fn map<U>(&self, f: impl FnOnce(&T) -> U) -> TimeFrame<U> {
+        match self {
+            TimeFrame::Week(t) => TimeFrame::Week(f(t)),
+            TimeFrame::Month(t) => TimeFrame::Month(f(t)),
+            TimeFrame::Quarter(t) => TimeFrame::Quarter(f(t)),
+            TimeFrame::Year(t) => TimeFrame::Year(f(t)),
+            TimeFrame::Range(t) => TimeFrame::Range(f(t)),
         }
-        .to_string();
+    }
+
+    /// Used as the first part of output filenames
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeFrame::Week(_) => "weekly",
+            TimeFrame::Month(_) => "monthly",
+            TimeFrame::Quarter(_) => "quarterly",
+            TimeFrame::Year(_) => "yearly",
+            TimeFrame::Range(_) => "range",
+        }
+    }
+
+    /// How a day is labelled on chart axes for this kind of time frame
+    pub fn day_label(&self, date: NaiveDate) -> String {
+        match self {
+            TimeFrame::Week(_) => date.format("%a %d").to_string(),
+            TimeFrame::Month(_) => date.format("%d").to_string(),
+            _ => date.format("%b %d").to_string(),
+        }
+    }
+}
+
+/// Inclusive run of days
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct DateSpan {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateSpan {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        DateSpan { start, end }
+    }
+
+    pub fn days(&self) -> u32 {
+        ((self.end - self.start).num_days() + 1).max(0) as u32
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start && date <= self.end
+    }
+
+    /// 1 based position of `date` inside the span
+    pub fn day_number(&self, date: NaiveDate) -> u32 {
+        ((date - self.start).num_days() + 1) as u32
+    }
 
-        // Format day with leading zero if needed
-        let day = format!("{:02}", now.day());
+    pub fn nth_day(&self, day_number: u32) -> NaiveDate {
+        self.start + Days::new(day_number.saturating_sub(1) as u64)
+    }
+
+    /// Every day of the span in order
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> {
+        self.start.iter_days().take(self.days() as usize)
+    }
 
-        CurrentDate { year, month, day }
+    /// Days of the span a `step` date axis marks: each day, each Monday or each first of the
+    /// month
+    pub fn ticks(&self, step: TickStep) -> Vec<NaiveDate> {
+        self.dates()
+            .filter(|date| match step {
+                TickStep::Day => true,
+                TickStep::Week => date.weekday() == Weekday::Mon,
+                TickStep::Month => date.day() == 1,
+            })
+            .collect()
     }
 }
 
-fn month_name_to_num(month_name: &str) -> u32 {
-    let month_number = match month_name {
+/// Spacing of the labelled ticks on a date axis
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TickStep {
+    Day,
+    Week,
+    Month,
+}
+
+impl TickStep {
+    /// Days for spans up to two weeks, weeks up to a quarter and months beyond
+    pub fn for_span(span: &DateSpan) -> Self {
+        match span.days() {
+            0..=14 => TickStep::Day,
+            15..=92 => TickStep::Week,
+            _ => TickStep::Month,
+        }
+    }
+
+    /// Label of a tick on `date` in `span`. The first tick of the axis and of every year also
+    /// name the year, so an axis reads right wherever it starts and runs over new year.
+    pub fn label(&self, date: NaiveDate, span: &DateSpan) -> String {
+        let previous = match self {
+            TickStep::Day => date - Days::new(1),
+            TickStep::Week => date - Days::new(7),
+            TickStep::Month => date - Months::new(1),
+        };
+        let new_year = previous < span.start || previous.year() != date.year();
+        let format = match (self, new_year) {
+            (TickStep::Day, false) => "%a %d",
+            (TickStep::Day, true) => "%a %d %Y",
+            (TickStep::Week, false) => "%d %b",
+            (TickStep::Week, true) => "%d %b %Y",
+            (TickStep::Month, false) => "%b",
+            (TickStep::Month, true) => "%b %Y",
+        };
+        date.format(format).to_string()
+    }
+}
+
+impl TimeFrame<DateSpan> {
+    /// Short unique label, e.g. 2025-W14, 2025-March, 2025-Q1, 2025
+    pub fn label(&self) -> String {
+        let span = self.inner();
+        let start = span.start;
+        match self {
+            TimeFrame::Week(_) => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            TimeFrame::Month(_) => format!("{}-{}", start.year(), month_num_to_name(start.month())),
+            TimeFrame::Quarter(_) => format!("{}-Q{}", start.year(), quarter(start)),
+            TimeFrame::Year(_) => start.year().to_string(),
+            TimeFrame::Range(_) => format!("{}_{}", span.start, span.end),
+        }
+    }
+
+    /// Chart caption, e.g. Week 14 2025, March 2025, Q1 2025
+    pub fn doc_title(&self) -> String {
+        let span = self.inner();
+        let start = span.start;
+        match self {
+            TimeFrame::Week(_) => {
+                let week = start.iso_week();
+                format!("Week {} {}", week.week(), week.year())
+            }
+            TimeFrame::Month(_) => format!("{} {}", month_num_to_name(start.month()), start.year()),
+            TimeFrame::Quarter(_) => format!("Q{} {}", quarter(start), start.year()),
+            TimeFrame::Year(_) => start.year().to_string(),
+            TimeFrame::Range(_) => format!("{} to {}", span.start, span.end),
+        }
+    }
+}
+
+/// The kinds of calendar time frames reports can be cut into
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameKind {
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl FromStr for FrameKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "week" => Ok(FrameKind::Week),
+            "month" => Ok(FrameKind::Month),
+            "quarter" => Ok(FrameKind::Quarter),
+            "year" => Ok(FrameKind::Year),
+            _ => Err(format!(
+                "unknown time frame {s}, expected week, month, quarter or year"
+            )),
+        }
+    }
+}
+
+fn quarter(date: NaiveDate) -> u32 {
+    (date.month() - 1) / 3 + 1
+}
+
+/// First day of the calendar period of `kind` that `date` falls in
+fn period_start(kind: FrameKind, date: NaiveDate) -> NaiveDate {
+    match kind {
+        FrameKind::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        FrameKind::Month => date.with_day(1).unwrap(),
+        FrameKind::Quarter => {
+            NaiveDate::from_ymd_opt(date.year(), (quarter(date) - 1) * 3 + 1, 1).unwrap()
+        }
+        FrameKind::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+    }
+}
+
+fn next_period_start(kind: FrameKind, start: NaiveDate) -> NaiveDate {
+    match kind {
+        FrameKind::Week => start + Days::new(7),
+        FrameKind::Month => start + Months::new(1),
+        FrameKind::Quarter => start + Months::new(3),
+        FrameKind::Year => start + Months::new(12),
+    }
+}
+
+/// Every calendar period of `kind` touching `first..=last`. Years are cut to `first..=last`
+/// so a yearly chart doesn't carry months of empty rows, the shorter periods are shown whole.
+pub fn time_frames(kind: FrameKind, first: NaiveDate, last: NaiveDate) -> Vec<TimeFrame<DateSpan>> {
+    let mut frames = vec![];
+    let mut start = period_start(kind, first);
+    while start <= last {
+        let next = next_period_start(kind, start);
+        let end = next.pred_opt().unwrap();
+        let frame = match kind {
+            FrameKind::Week => TimeFrame::Week(DateSpan::new(start, end)),
+            FrameKind::Month => TimeFrame::Month(DateSpan::new(start, end)),
+            FrameKind::Quarter => TimeFrame::Quarter(DateSpan::new(start, end)),
+            FrameKind::Year => TimeFrame::Year(DateSpan::new(start.max(first), end.min(last))),
+        };
+        frames.push(frame);
+        start = next;
+    }
+    frames
+}
+
+pub fn month_num_to_name(month_number: u32) -> &'static str {
+    match month_number {
+        1 => "January",
+        2 => "February",
+        3 => "March",
+        4 => "April",
+        5 => "May",
+        6 => "June",
+        7 => "July",
+        8 => "August",
+        9 => "September",
+        10 => "October",
+        11 => "November",
+        12 => "December",
+        _ => panic!("Invalid month number: {}", month_number),
+    }
+}
+
+pub fn month_name_to_num(month_name: &str) -> u32 {
+    match month_name {
         "January" => 1,
         "February" => 2,
         "March" => 3,
@@ -56,66 +280,54 @@ fn month_name_to_num(month_name: &str) -> u32 {
         "November" => 11,
         "December" => 12,
         _ => panic!("Invalid month name: {}", month_name),
-    };
-    month_number
-}
-
-/// Returns an array of days for the given month, accounting for leap years
-pub fn days_in_month(month_name: &str) -> Vec<u32> {
-    let now = Local::now();
-    let year = now.year();
-
-    // Parse month name to month number
-    let month_number = month_name_to_num(month_name);
-
-    // Get the last day of the month
-    let days_in_month = if month_number == 12 {
-        // For December, get days by creating Jan 1 of next year and going back 1 day
-        NaiveDate::from_ymd_opt(year + 1, 1, 1)
-            .unwrap()
-            .pred_opt()
-            .unwrap()
-            .day()
-    } else {
-        // For other months, get days by creating 1st of next month and going back 1 day
-        NaiveDate::from_ymd_opt(year, month_number + 1, 1)
-            .unwrap()
-            .pred_opt()
-            .unwrap()
-            .day()
-    };
-
-    // Create and return the array of days
-    (1..=days_in_month).collect()
+    }
 }
 
-pub fn day_of_year(performance: &HabitDayPerformance) -> u32 {
-    // Parse the year, month, and day from strings
-    let year: u32 = performance.year.parse().unwrap();
-    let day: u32 = performance.day.parse().unwrap();
-
-    // Convert month name to month number (1-12)
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let month_number = month_name_to_num(&performance.month);
-
-    // println!("day {}, month {}, year {}", day, month_number, year);
-
-    // Days in each month (non-leap year)
-    let days_in_month = [0, 31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
 
-    // Check if it's a leap year
-    let is_leap_year = (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0);
+    #[test]
+    fn shorter_periods_are_shown_whole() {
+        let weeks = time_frames(FrameKind::Week, date("2025-03-05"), date("2025-03-11"));
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(
+            *weeks[0].inner(),
+            DateSpan::new(date("2025-03-03"), date("2025-03-09"))
+        );
+        assert_eq!(weeks[1].label(), "2025-W11");
 
-    // Calculate day of year
-    let mut day_of_year = day;
-    for m in 1..month_number {
-        day_of_year += days_in_month[m as usize];
+        let quarters = time_frames(FrameKind::Quarter, date("2025-02-10"), date("2025-04-01"));
+        assert_eq!(quarters.len(), 2);
+        assert_eq!(quarters[0].inner().days(), 90);
+        assert_eq!(quarters[1].doc_title(), "Q2 2025");
     }
 
-    // Add leap day if it's a leap year and we're past February
-    if is_leap_year && month_number > 2 {
-        day_of_year += 1;
+    #[test]
+    fn years_are_cut_to_the_recorded_days() {
+        let years = time_frames(FrameKind::Year, date("2024-11-20"), date("2025-02-03"));
+        assert_eq!(years.len(), 2);
+        assert_eq!(
+            *years[0].inner(),
+            DateSpan::new(date("2024-11-20"), date("2024-12-31"))
+        );
+        assert_eq!(
+            *years[1].inner(),
+            DateSpan::new(date("2025-01-01"), date("2025-02-03"))
+        );
+        assert_eq!(years[1].label(), "2025");
     }
 
-    day_of_year
+    #[test]
+    fn ranges_are_labelled_by_their_ends() {
+        let range = TimeFrame::Range(DateSpan::new(date("2025-03-01"), date("2025-03-10")));
+        assert_eq!(range.label(), "2025-03-01_2025-03-10");
+        assert_eq!(range.inner().nth_day(10), date("2025-03-10"));
+        assert_eq!(range.inner().day_number(date("2025-03-04")), 4);
+        assert!("fortnight".parse::<FrameKind>().is_err());
+    }
 }
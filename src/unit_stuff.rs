@@ -0,0 +1,97 @@
+//! Units objective values are written in and converting between them
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+const KG_PER_LB: f32 = 0.453_592_37;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Unit {
+    #[serde(rename = "kg")]
+    Kg,
+    #[serde(rename = "lb")]
+    Lb,
+    #[serde(rename = "°C", alias = "C")]
+    Celsius,
+    #[serde(rename = "°F", alias = "F")]
+    Fahrenheit,
+    /// Anything else, kept as written and never converted
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unit = match s.trim() {
+            "" => return Err("empty unit".to_string()),
+            "kg" | "kgs" | "Kg" | "KG" => Unit::Kg,
+            "lb" | "lbs" | "Lb" | "LB" => Unit::Lb,
+            "°C" | "C" | "℃" | "degC" => Unit::Celsius,
+            "°F" | "F" | "℉" | "degF" => Unit::Fahrenheit,
+            other => Unit::Other(other.to_string()),
+        };
+        Ok(unit)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Kg => write!(f, "kg"),
+            Unit::Lb => write!(f, "lb"),
+            Unit::Celsius => write!(f, "°C"),
+            Unit::Fahrenheit => write!(f, "°F"),
+            Unit::Other(unit) => write!(f, "{unit}"),
+        }
+    }
+}
+
+/// A value with the unit it was written in
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Quantity {
+    pub value: f32,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    /// Same amount in `unit`, `None` when the two units don't measure the same thing
+    pub fn convert(&self, unit: &Unit) -> Option<Quantity> {
+        let value = match (&self.unit, unit) {
+            (from, to) if from == to => self.value,
+            (Unit::Kg, Unit::Lb) => self.value / KG_PER_LB,
+            (Unit::Lb, Unit::Kg) => self.value * KG_PER_LB,
+            (Unit::Celsius, Unit::Fahrenheit) => self.value * 9.0 / 5.0 + 32.0,
+            (Unit::Fahrenheit, Unit::Celsius) => (self.value - 32.0) * 5.0 / 9.0,
+            _ => return None,
+        };
+        Some(Quantity {
+            value,
+            unit: unit.clone(),
+        })
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.value, self.unit)
+    }
+}
+
+static VALUE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*([-+]?(?:\d+\.?\d*|\.\d+))(?:\s*([^\s\d@,;()]+))?(?:[\s@]|$)").unwrap()
+});
+
+/// Reads a written value like `82.45 kg`, `181lb`, `-1.5` or `+3`, returning the number and
+/// the unit right after it if there is one. Anything after the unit is ignored, a number
+/// running into something else like `82,5` isn't a value.
+pub fn parse_value(text: &str) -> Option<(f32, Option<Unit>)> {
+    let cap = VALUE_RE.captures(text)?;
+    let value = cap[1].parse().ok()?;
+    let unit = cap.get(2).and_then(|u| u.as_str().parse().ok());
+    Some((value, unit))
+}
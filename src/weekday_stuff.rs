@@ -0,0 +1,206 @@
+//! Habit completion and objective values by day of the week
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::data_stuff::{Aggregate, HabitDayPerformance};
+use crate::schedule_stuff::HabitStatus;
+
+/// Monday to Sunday, the order weekday charts and arrays use
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Done versus expected counts of one habit for each weekday, Monday first
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeekdayRates {
+    pub title: String,
+    pub done: [u32; 7],
+    pub expected: [u32; 7],
+}
+
+impl WeekdayRates {
+    pub fn rate(&self, day: Weekday) -> Option<f32> {
+        let i = day.num_days_from_monday() as usize;
+        match self.expected[i] {
+            0 => None,
+            expected => Some(self.done[i] as f32 / expected as f32),
+        }
+    }
+
+    /// Weekday with the highest completion rate, the earliest one on a tie
+    pub fn best(&self) -> Option<Weekday> {
+        extreme(|day| self.rate(day), |a, b| a > b)
+    }
+
+    /// Weekday with the lowest completion rate, the earliest one on a tie
+    pub fn worst(&self) -> Option<Weekday> {
+        extreme(|day| self.rate(day), |a, b| a < b)
+    }
+
+    /// Completion rates Monday to Friday and over the weekend
+    pub fn weekend(&self) -> (Option<f32>, Option<f32>) {
+        let rate = |days: std::ops::Range<usize>| {
+            let done: u32 = self.done[days.clone()].iter().sum();
+            match self.expected[days].iter().sum::<u32>() {
+                0 => None,
+                expected => Some(done as f32 / expected as f32),
+            }
+        };
+        (rate(0..5), rate(5..7))
+    }
+
+    /// Counts of several habits added up
+    pub fn total<'a>(title: &str, rates: impl IntoIterator<Item = &'a WeekdayRates>) -> Self {
+        let mut total = WeekdayRates {
+            title: title.to_string(),
+            done: [0; 7],
+            expected: [0; 7],
+        };
+        for r in rates {
+            for i in 0..7 {
+                total.done[i] += r.done[i];
+                total.expected[i] += r.expected[i];
+            }
+        }
+        total
+    }
+
+    fn describe(&self, day: Weekday) -> String {
+        let i = day.num_days_from_monday() as usize;
+        format!(
+            "{day} {:.0}% ({}/{})",
+            self.rate(day).unwrap_or_default() * 100.0,
+            self.done[i],
+            self.expected[i]
+        )
+    }
+}
+
+impl fmt::Display for WeekdayRates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.best(), self.worst()) {
+            (Some(best), Some(worst)) if self.rate(best) == self.rate(worst) => write!(
+                f,
+                "{}: {:.0}% on every weekday",
+                self.title,
+                self.rate(best).unwrap_or_default() * 100.0
+            ),
+            (Some(best), Some(worst)) => write!(
+                f,
+                "{}: best {}, worst {}",
+                self.title,
+                self.describe(best),
+                self.describe(worst)
+            ),
+            _ => write!(f, "{}: nothing expected", self.title),
+        }
+    }
+}
+
+/// Weekday counts from the statuses of one habit. Days not expected or not tracked don't
+/// count, a partly done group counts its done and expected instances.
+pub fn weekday_rates(title: &str, statuses: &BTreeMap<NaiveDate, HabitStatus>) -> WeekdayRates {
+    let mut rates = WeekdayRates::total(title, []);
+    for (date, status) in statuses {
+        let (done, expected) = match status {
+            HabitStatus::Done => (1, 1),
+            HabitStatus::Missed | HabitStatus::Unrecorded => (0, 1),
+            HabitStatus::Partial(done, expected) => (*done, *expected),
+            HabitStatus::NotScheduled | HabitStatus::Inactive => continue,
+        };
+        let i = date.weekday().num_days_from_monday() as usize;
+        rates.done[i] += done;
+        rates.expected[i] += expected;
+    }
+    rates
+}
+
+/// Day values of one objective sorted by weekday, Monday first
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeekdayValues {
+    pub title: String,
+    pub values: [Vec<f32>; 7],
+}
+
+impl WeekdayValues {
+    pub fn mean(&self, day: Weekday) -> Option<f32> {
+        Aggregate::Mean.apply(&self.values[day.num_days_from_monday() as usize])
+    }
+
+    /// Weekday with the highest mean, the earliest one on a tie
+    pub fn highest(&self) -> Option<Weekday> {
+        extreme(|day| self.mean(day), |a, b| a > b)
+    }
+
+    /// Weekday with the lowest mean, the earliest one on a tie
+    pub fn lowest(&self) -> Option<Weekday> {
+        extreme(|day| self.mean(day), |a, b| a < b)
+    }
+}
+
+impl fmt::Display for WeekdayValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |day: Weekday| {
+            format!(
+                "{day} {:.1} ({} days)",
+                self.mean(day).unwrap_or_default(),
+                self.values[day.num_days_from_monday() as usize].len()
+            )
+        };
+        match (self.highest(), self.lowest()) {
+            (Some(highest), Some(lowest)) => write!(
+                f,
+                "{}: highest {}, lowest {}",
+                self.title,
+                describe(highest),
+                describe(lowest)
+            ),
+            _ => write!(f, "{}: no values", self.title),
+        }
+    }
+}
+
+/// Values of `objective` in `hdps`, one per day combined with `aggregate`, by weekday
+pub fn weekday_values(
+    hdps: &[HabitDayPerformance],
+    objective: &str,
+    aggregate: Aggregate,
+) -> WeekdayValues {
+    let mut values: [Vec<f32>; 7] = Default::default();
+    for hdp in hdps {
+        if let Some(value) = hdp.objective_by(objective, aggregate) {
+            values[hdp.date().weekday().num_days_from_monday() as usize].push(value);
+        }
+    }
+    WeekdayValues {
+        title: objective.to_string(),
+        values,
+    }
+}
+
+/// First weekday whose value beats every other by `better`
+fn extreme(
+    value: impl Fn(Weekday) -> Option<f32>,
+    better: fn(f32, f32) -> bool,
+) -> Option<Weekday> {
+    WEEKDAYS
+        .into_iter()
+        .filter_map(|day| Some((day, value(day)?)))
+        .fold(
+            None,
+            |found: Option<(Weekday, f32)>, (day, v)| match found {
+                Some((_, best)) if !better(v, best) => found,
+                _ => Some((day, v)),
+            },
+        )
+        .map(|(day, _)| day)
+}
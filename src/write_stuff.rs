@@ -0,0 +1,196 @@
+//! Writing org files back into the notes, new days and weekly reviews
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::data_stuff::{day_file_path, Aggregate, HabitDayPerformance};
+use crate::schedule_stuff::HabitStats;
+
+/// Renders a fresh day file in the layout `process_org_file` reads back.
+/// Habits start out as TODO and objectives get an empty `- ` slot to fill in.
+pub fn day_template(date: NaiveDate, habits: &[&str], objectives: &[&str]) -> String {
+    let mut org = String::new();
+    org.push_str(&format!("#+TITLE: {}\n\n", date.format("%A, %B %d %Y")));
+
+    org.push_str("* Habits\n");
+    for habit in habits {
+        org.push_str(&format!("** TODO /HABIT/ {habit}\n"));
+    }
+
+    org.push_str("\n* Objectives\n");
+    for objective in objectives {
+        org.push_str(&format!("** /OBJECTIVE/ {objective}\n- \n"));
+    }
+    org
+}
+
+/// Writes the template for `date` into the notes tree, refusing to clobber a day that already exists
+pub fn write_day_file(
+    root: &Path,
+    date: NaiveDate,
+    habits: &[&str],
+    objectives: &[&str],
+) -> Result<PathBuf, String> {
+    let path = day_file_path(root, date);
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+    fs::write(&path, day_template(date, habits, objectives))
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// Lays out an org table with every column padded to its widest cell
+fn org_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        format!("| {} |\n", padded.join(" | "))
+    };
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+    let mut table = line(headers.to_vec());
+    table.push_str(&format!("|-{}-|\n", rule.join("-+-")));
+    for row in rows {
+        table.push_str(&line(row.iter().map(|c| c.as_str()).collect()));
+    }
+    table
+}
+
+/// Org link from the reviews folder back to a day file
+fn day_link(hdp: &HabitDayPerformance) -> String {
+    format!(
+        "[[file:../{}/{}/{}.org][{}]]",
+        hdp.year,
+        hdp.month,
+        hdp.day,
+        hdp.date().format("%a %d %b")
+    )
+}
+
+fn average(hdps: &[HabitDayPerformance], objective: &str, aggregate: Aggregate) -> Option<f32> {
+    let values: Vec<f32> = hdps
+        .iter()
+        .filter_map(|h| h.objective_by(objective, aggregate))
+        .collect();
+    Aggregate::Mean.apply(&values)
+}
+
+/// Everything a weekly review needs, already cut down to the week and the one before it
+#[derive(Debug, PartialEq, Serialize)]
+pub struct WeeklyReview<'a> {
+    pub title: String,
+    pub habits: Vec<HabitStats>,
+    /// Objective titles with how each one's day value is taken
+    pub objectives: Vec<(&'a str, Aggregate)>,
+    pub week: &'a [HabitDayPerformance],
+    pub previous_week: &'a [HabitDayPerformance],
+}
+
+impl WeeklyReview<'_> {
+    /// Renders the review as an org document
+    pub fn to_org(&self) -> String {
+        let mut org = format!("#+TITLE: Weekly review {}\n\n", self.title);
+
+        org.push_str("* Habits\n");
+        let habit_rows: Vec<Vec<String>> = self
+            .habits
+            .iter()
+            .map(|h| {
+                let rate = match h.expected {
+                    0 => String::new(),
+                    _ => format!("{:.0}%", h.rate() * 100.0),
+                };
+                vec![
+                    h.title.clone(),
+                    h.done.to_string(),
+                    h.expected.to_string(),
+                    rate,
+                ]
+            })
+            .collect();
+        org.push_str(&org_table(&["Habit", "Done", "Total", "Rate"], &habit_rows));
+
+        org.push_str("\n* Objectives\n");
+        let fmt = |v: Option<f32>| v.map(|v| format!("{v:.1}")).unwrap_or_default();
+        let objective_rows: Vec<Vec<String>> = self
+            .objectives
+            .iter()
+            .map(|(o, aggregate)| {
+                let this_week = average(self.week, o, *aggregate);
+                let last_week = average(self.previous_week, o, *aggregate);
+                let delta = match (this_week, last_week) {
+                    (Some(t), Some(l)) => format!("{:+.1}", t - l),
+                    _ => String::new(),
+                };
+                vec![o.to_string(), fmt(this_week), fmt(last_week), delta]
+            })
+            .collect();
+        org.push_str(&org_table(
+            &["Objective", "Average", "Previous week", "Delta"],
+            &objective_rows,
+        ));
+
+        org.push_str("\n* Days\n");
+        let scored: Vec<(&HabitDayPerformance, u32, u32)> = self
+            .week
+            .iter()
+            .map(|h| {
+                let (done, total) = h.done_count();
+                (h, done, total)
+            })
+            .collect();
+        let share = |d: u32, t: u32| if t == 0 { 0.0 } else { d as f32 / t as f32 };
+        let best = scored
+            .iter()
+            .max_by(|a, b| share(a.1, a.2).total_cmp(&share(b.1, b.2)));
+        let worst = scored
+            .iter()
+            .min_by(|a, b| share(a.1, a.2).total_cmp(&share(b.1, b.2)));
+        if let (Some(best), Some(worst)) = (best, worst) {
+            org.push_str(&format!(
+                "- Best day: {} ({}/{} habits)\n",
+                day_link(best.0),
+                best.1,
+                best.2
+            ));
+            org.push_str(&format!(
+                "- Worst day: {} ({}/{} habits)\n",
+                day_link(worst.0),
+                worst.1,
+                worst.2
+            ));
+        }
+        for (hdp, done, total) in &scored {
+            org.push_str(&format!("- {} {done}/{total}\n", day_link(hdp)));
+        }
+        org
+    }
+}
+
+/// Writes a review into `{root}/reviews/{label}.org`, keeping an existing one unless `force` is set
+pub fn write_review(root: &Path, label: &str, org: &str, force: bool) -> Result<PathBuf, String> {
+    let dir = root.join("reviews");
+    let path = dir.join(format!("{label}.org"));
+    if path.exists() && !force {
+        return Err(format!("{} already exists", path.display()));
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    fs::write(&path, org).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(path)
+}
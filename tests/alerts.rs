@@ -0,0 +1,192 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{date, fixture_config, fixture_progress};
+use org_analyzer::alert_stuff::{dispatch, evaluate, Alert, AlertOutputs, Rule, Severity};
+use org_analyzer::config_stuff::Config;
+use std::fs;
+
+fn rules(toml: &str) -> Vec<Rule> {
+    toml::from_str::<Config>(toml).unwrap().alerts
+}
+
+#[test]
+fn rules_fire_on_runs_up_to_the_latest_day() {
+    let rules = rules(
+        r##"
+        [[alerts]]
+        name = "GERD flare"
+        when = { kind = "objective_above", objective = "GERD-Symptoms(0-10)=", value = 6.0 }
+        severity = "warning"
+
+        [[alerts]]
+        when = { kind = "objective_above", objective = "GERD-Symptoms(0-10)=", value = 6.0 }
+        days = 2
+
+        [[alerts]]
+        when = { kind = "objective_below", objective = "Weight=", value = 81.0 }
+        days = 3
+
+        [[alerts]]
+        when = { kind = "habit_missed", habit = "#2 Walk" }
+        days = 2
+        severity = "critical"
+
+        [[alerts]]
+        when = { kind = "habit_missed", habit = "Swim" }
+        "##,
+    );
+    let progress = fixture_progress();
+    let alerts = evaluate(&rules, progress.hpds(), &fixture_config());
+    let end = date("2025-03-09");
+    assert_eq!(
+        alerts,
+        [
+            Alert {
+                rule: "GERD flare".to_string(),
+                severity: Severity::Warning,
+                start: end,
+                end,
+                days: 1,
+            },
+            // 2025-03-06 has no weight and is stepped over, the missing 2025-03-02 ends the run
+            Alert {
+                rule: "Weight= below 81".to_string(),
+                severity: Severity::Info,
+                start: date("2025-03-03"),
+                end,
+                days: 6,
+            },
+            Alert {
+                rule: "#2 Walk missed".to_string(),
+                severity: Severity::Critical,
+                start: date("2025-03-08"),
+                end,
+                days: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn habit_rules_need_a_known_habit() {
+    let config: Config = toml::from_str(
+        r##"
+        [[alerts]]
+        when = { kind = "habit_missed", habit = "Walk" }
+        "##,
+    )
+    .unwrap();
+    let error = config.validate().unwrap_err();
+    assert!(error.contains("Walk missed"), "{error}");
+
+    let config: Config = toml::from_str(
+        r##"
+        [habits."Parasym"]
+        start = "2025-04-01"
+
+        [[alerts]]
+        when = { kind = "habit_missed", habit = "#1 Walk" }
+
+        [[alerts]]
+        when = { kind = "habit_missed", habit = "Parasym" }
+
+        [[alerts]]
+        when = { kind = "habit_missed", habit = "anki  ukrainian" }
+
+        [[alerts]]
+        when = { kind = "objective_below", objective = "weight=", value = 80.0 }
+
+        [aliases]
+        "Anki Ukrainian" = "Anki Ukrainian Lesson"
+        "##,
+    )
+    .unwrap();
+    assert_eq!(config.validate(), Ok(()));
+
+    let config: Config = toml::from_str(
+        r##"
+        [[alerts]]
+        when = { kind = "objective_above", objective = "GERD=", value = 6.0 }
+        "##,
+    )
+    .unwrap();
+    let error = config.validate().unwrap_err();
+    assert!(error.contains("known objective"), "{error}");
+}
+
+#[test]
+fn weekly_habits_alert_on_short_weeks() {
+    let config = |times: u32| -> Config {
+        toml::from_str(&format!(
+            r##"
+            [habits."Swim"]
+            schedule = {{ kind = "per_week", times = {times} }}
+
+            [[alerts]]
+            when = {{ kind = "habit_missed", habit = "Swim" }}
+            "##
+        ))
+        .unwrap()
+    };
+    let progress = fixture_progress();
+    let fired = |times: u32| -> Vec<(NaiveDate, u32)> {
+        let config = config(times);
+        evaluate(&config.alerts, progress.hpds(), &config)
+            .iter()
+            .map(|alert| (alert.start, alert.days))
+            .collect()
+    };
+
+    // 2025-W10 ends on the last day with two swims, enough for a target of two
+    assert_eq!(fired(2), []);
+    assert_eq!(fired(3), [(date("2025-03-03"), 1)]);
+    // Every week falls short of four, 2025-W07 by three with only four day files
+    assert_eq!(fired(4), [(date("2025-01-27"), 10)]);
+}
+
+#[test]
+fn alerts_are_appended_to_the_file_output() {
+    let path = std::env::temp_dir().join(format!("alerts-{}.log", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let outputs = AlertOutputs {
+        stdout: false,
+        file: Some(path.clone()),
+        command: None,
+        webhook: None,
+    };
+
+    dispatch(&[], &outputs).unwrap();
+    assert!(!path.exists(), "nothing to send leaves the outputs alone");
+
+    let alert = Alert {
+        rule: "GERD flare".to_string(),
+        severity: Severity::Warning,
+        start: date("2025-03-08"),
+        end: date("2025-03-09"),
+        days: 2,
+    };
+    dispatch(std::slice::from_ref(&alert), &outputs).unwrap();
+    dispatch(&[alert], &outputs).unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        written,
+        "[warning] GERD flare for 2 days (2025-03-08 to 2025-03-09)\n".repeat(2)
+    );
+
+    let broken = AlertOutputs {
+        webhook: Some("https://example.com/hook".to_string()),
+        ..outputs
+    };
+    let alert = Alert {
+        rule: "x".to_string(),
+        severity: Severity::Info,
+        start: date("2025-03-09"),
+        end: date("2025-03-09"),
+        days: 1,
+    };
+    let path = broken.file.clone().unwrap();
+    assert!(dispatch(&[alert], &broken).is_err());
+    fs::remove_file(&path).unwrap();
+}
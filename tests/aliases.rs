@@ -0,0 +1,87 @@
+mod common;
+
+use common::date;
+use org_analyzer::analyze_stuff::Analyzer;
+use org_analyzer::config_stuff::Config;
+use org_analyzer::data_stuff::{HabitDayPerformance, Performance, Task, TaskPerformance};
+use org_analyzer::lint_stuff::lint_day;
+use org_analyzer::source_stuff::MemorySource;
+use std::path::Path;
+
+fn day(d: &str, habits: &[(&str, Performance)]) -> HabitDayPerformance {
+    let mut hdp = HabitDayPerformance::new(date(d));
+    hdp.todo_performance = habits
+        .iter()
+        .map(|(title, p)| TaskPerformance::new(Task::Todo(title.to_string()), p.clone()))
+        .collect();
+    hdp
+}
+
+fn config() -> Config {
+    toml::from_str(
+        r#"
+        [aliases]
+        "Anki Ukrainian" = "Anki Ukrainian Lesson"
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn renamed_habit_keeps_one_history() {
+    let source = MemorySource::new(vec![
+        day("2025-03-01", &[("Anki Ukrainian", Performance::Done)]),
+        day("2025-03-02", &[("anki   ukrainian", Performance::Fail)]),
+        day(
+            "2025-03-03",
+            &[("Anki Ukrainian Lesson", Performance::Done)],
+        ),
+        day(
+            "2025-03-04",
+            &[("ANKI UKRAINIAN LESSON ", Performance::Done)],
+        ),
+    ]);
+    let a = Analyzer::from_source(&source).with_config(config());
+
+    let stats = a.habit_stats("Anki Ukrainian Lesson");
+    assert_eq!((stats.done, stats.expected), (3, 4));
+    assert_eq!(
+        a.progress().all_unique_todo_titles(),
+        vec!["Anki Ukrainian Lesson".to_string()]
+    );
+}
+
+#[test]
+fn old_and_new_title_on_one_day_fold_into_done() {
+    let source = MemorySource::new(vec![day(
+        "2025-03-01",
+        &[
+            ("Anki Ukrainian", Performance::Fail),
+            ("Anki Ukrainian Lesson", Performance::Done),
+        ],
+    )]);
+    let a = Analyzer::from_source(&source).with_config(config());
+
+    let hdp = &a.progress().hpds()[0];
+    assert_eq!(hdp.todo_performance.len(), 1);
+    assert_eq!(hdp.done_count(), (1, 1));
+}
+
+#[test]
+fn canonical_title_normalises_known_titles() {
+    let config = config();
+    assert_eq!(config.canonical_title("  #1   walk "), "#1 Walk");
+    assert_eq!(config.canonical_title("weight="), "Weight=");
+    assert_eq!(config.canonical_title("Juggling"), "Juggling");
+}
+
+#[test]
+fn lint_accepts_aliases() {
+    let org = "** DONE /HABIT/ Anki Ukrainian\n** DONE /HABIT/ Anki Ukrainian Lesson\n";
+    let issues = lint_day(Path::new("2025/March/01.org"), org, &config());
+    let report: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+    assert_eq!(
+        report,
+        vec!["2025/March/01.org:2: habit \"Anki Ukrainian Lesson\" already listed on line 1"]
+    );
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::{assert_golden, date, fixture_analyzer};
+use org_analyzer::analyze_stuff::{
+    Analyzer, Overlay, OBJECTIVE_GRID_SIZE, OBJECTIVE_SIZE, TIME_OF_DAY_SIZE, WEEKDAY_SIZE,
+};
+use org_analyzer::time_stuff::{time_frames, DateSpan, FrameKind, TickStep, TimeFrame};
+use plotters::prelude::*;
+
+fn habits_svg(a: &Analyzer, label: &str, grouped: bool) -> String {
+    let frame = a.frame_by_label(label).unwrap();
+    let mut svg = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut svg, a.heatmap_size(&frame, grouped)).into_drawing_area();
+        a.draw_habits_on(&root, &frame, grouped);
+    }
+    svg
+}
+
+fn objective_svg(a: &Analyzer, label: &str, key: &str) -> String {
+    let frame = a.frame_by_label(label).unwrap();
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, OBJECTIVE_SIZE).into_drawing_area();
+        a.draw_objective_on(&root, &frame, key).unwrap();
+    }
+    svg
+}
+
+#[test]
+fn monthly_habit_heatmap_matches_golden() {
+    let a = fixture_analyzer();
+    assert_golden(
+        "charts/habits-2025-February.svg",
+        &habits_svg(&a, "2025-February", false),
+    );
+}
+
+#[test]
+fn weekly_group_heatmap_matches_golden() {
+    let a = fixture_analyzer();
+    assert_golden(
+        "charts/habit-groups-2025-W07.svg",
+        &habits_svg(&a, "2025-W07", true),
+    );
+}
+
+#[test]
+fn objective_chart_matches_golden() {
+    let a = fixture_analyzer();
+    assert_golden(
+        "charts/gerd-2025-W10.svg",
+        &objective_svg(&a, "2025-W10", "gerd"),
+    );
+}
+
+#[test]
+fn year_frames_cover_the_recorded_days() {
+    let a = fixture_analyzer();
+    let year = a.frame_by_label("2025").unwrap();
+    assert_eq!(
+        *year.inner(),
+        DateSpan::new(date("2025-01-27"), date("2025-03-09"))
+    );
+
+    let years = time_frames(FrameKind::Year, date("2024-11-02"), date("2025-03-09"));
+    let spans: Vec<DateSpan> = years.iter().map(|frame| *frame.inner()).collect();
+    assert_eq!(
+        spans,
+        [
+            DateSpan::new(date("2024-11-02"), date("2024-12-31")),
+            DateSpan::new(date("2025-01-01"), date("2025-03-09")),
+        ]
+    );
+}
+
+#[test]
+fn empty_spans_draw_a_blank_heatmap() {
+    let a = fixture_analyzer();
+    let frame = TimeFrame::Range(DateSpan::new(date("2025-03-10"), date("2025-03-01")));
+    let mut svg = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut svg, a.heatmap_size(&frame, false)).into_drawing_area();
+        a.draw_habits_on(&root, &frame, false);
+    }
+    assert!(!svg.contains("Mar 01"));
+}
+
+#[test]
+fn time_of_day_chart_matches_golden() {
+    let a = fixture_analyzer();
+    let frame = a.frame_by_label("2025-W10").unwrap();
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, TIME_OF_DAY_SIZE).into_drawing_area();
+        a.draw_time_of_day_on(&root, &frame);
+    }
+    assert_golden("charts/time-of-day-2025-W10.svg", &svg);
+}
+
+#[test]
+fn weekday_charts_match_golden() {
+    let a = fixture_analyzer();
+    let frame = a.frame_by_label("2025-March").unwrap();
+    let mut habits = String::new();
+    let mut mood = String::new();
+    {
+        let root = SVGBackend::with_string(&mut habits, WEEKDAY_SIZE).into_drawing_area();
+        a.draw_weekdays_on(&root, &frame);
+        let root = SVGBackend::with_string(&mut mood, WEEKDAY_SIZE).into_drawing_area();
+        a.draw_objective_weekdays_on(&root, &frame, "mood").unwrap();
+    }
+    assert_golden("charts/weekdays-2025-March.svg", &habits);
+    assert_golden("charts/weekdays-mood-2025-March.svg", &mood);
+}
+
+#[test]
+fn combined_objective_charts_match_golden() {
+    let a = fixture_analyzer();
+    let frame = a.frame_by_label("2025-February").unwrap();
+    let overlay: Overlay = "weight:gerd".parse().unwrap();
+    let mut grid = String::new();
+    let mut both = String::new();
+    {
+        let root = SVGBackend::with_string(&mut grid, OBJECTIVE_GRID_SIZE).into_drawing_area();
+        a.draw_objective_grid_on(&root, &frame);
+        let root = SVGBackend::with_string(&mut both, OBJECTIVE_SIZE).into_drawing_area();
+        a.draw_overlay_on(&root, &frame, &overlay).unwrap();
+    }
+    assert_golden("charts/objectives-2025-February.svg", &grid);
+    assert_golden("charts/weight-gerd-2025-February.svg", &both);
+
+    assert_eq!(overlay.to_string(), "weight:gerd");
+    assert!("weight".parse::<Overlay>().is_err());
+    assert!("weight:sleep".parse::<Overlay>().is_err());
+}
+
+#[test]
+fn date_ticks_run_over_new_year() {
+    let winter = DateSpan::new(date("2024-12-15"), date("2025-02-10"));
+    let step = TickStep::for_span(&winter);
+    assert_eq!(step, TickStep::Week);
+    let labels: Vec<String> = winter
+        .ticks(step)
+        .into_iter()
+        .map(|d| step.label(d, &winter))
+        .collect();
+    assert_eq!(
+        labels[..4],
+        ["16 Dec 2024", "23 Dec", "30 Dec", "06 Jan 2025"]
+    );
+    assert_eq!(labels.len(), 9);
+
+    let year = DateSpan::new(date("2024-07-01"), date("2025-06-30"));
+    let step = TickStep::for_span(&year);
+    let labels: Vec<String> = year
+        .ticks(step)
+        .into_iter()
+        .map(|d| step.label(d, &year))
+        .collect();
+    assert_eq!(labels[..2], ["Jul 2024", "Aug"]);
+    assert_eq!(labels[5..8], ["Dec", "Jan 2025", "Feb"]);
+    assert_eq!(labels.len(), 12);
+
+    let week = DateSpan::new(date("2024-12-30"), date("2025-01-05"));
+    assert_eq!(TickStep::for_span(&week), TickStep::Day);
+    let labels: Vec<String> = week
+        .ticks(TickStep::Day)
+        .into_iter()
+        .map(|d| TickStep::Day.label(d, &week))
+        .collect();
+    assert_eq!(labels.len(), 7);
+    assert_eq!(labels[..3], ["Mon 30 2024", "Tue 31", "Wed 01 2025"]);
+
+    // Half a year with no January still says which year it is
+    let autumn = DateSpan::new(date("2024-07-01"), date("2024-12-31"));
+    let step = TickStep::for_span(&autumn);
+    let labels: Vec<String> = autumn
+        .ticks(step)
+        .into_iter()
+        .map(|d| step.label(d, &autumn))
+        .collect();
+    assert_eq!(labels, ["Jul 2024", "Aug", "Sep", "Oct", "Nov", "Dec"]);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use chrono::NaiveTime;
+use common::{assert_golden, fixture_analyzer};
+use org_analyzer::clock_stuff::{clock_minutes, closed_time, format_minutes};
+use org_analyzer::data_stuff::process_org_file;
+
+fn time(s: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+}
+
+#[test]
+fn closed_and_clock_lines_land_on_their_habit() {
+    let org = "\
+** DONE /HABIT/ #1 Walk
+   CLOSED: [2025-03-14 Fri 08:12]
+   :LOGBOOK:
+   CLOCK: [2025-03-14 Fri 07:50]--[2025-03-14 Fri 08:12] =>  0:22
+   CLOCK: [2025-03-14 Fri 06:00]--[2025-03-14 Fri 06:10] =>  0:10
+   CLOCK: [2025-03-14 Fri 09:00]
+   :END:
+** FAIL /HABIT/ #2 Walk
+";
+    let habits = &process_org_file(org.to_string())[0];
+    assert_eq!(habits[0].time(), Some(time("08:12")));
+    assert_eq!(habits[0].clocked_minutes(), 32);
+    assert_eq!(habits[1].time(), None);
+    assert_eq!(habits[1].clocked_minutes(), 0);
+
+    assert_eq!(
+        closed_time("CLOSED: [2025-03-14 Fri 23:05] SCHEDULED: <2025-03-14 Fri>"),
+        Some(time("23:05"))
+    );
+    // A clock running past midnight still counts its minutes
+    assert_eq!(
+        clock_minutes("CLOCK: [2025-03-14 Fri 23:50]--[2025-03-15 Sat 00:20] =>  0:30"),
+        Some(30)
+    );
+    assert_eq!(format_minutes(65), "1h 05m");
+}
+
+#[test]
+fn habit_times_take_the_median() {
+    let walk = fixture_analyzer().habit_times("#1 Walk");
+    assert_eq!(
+        walk.times,
+        vec![time("07:15"), time("07:40"), time("08:05"), time("09:30")]
+    );
+    assert_eq!(walk.typical(), NaiveTime::from_hms_opt(7, 52, 30));
+}
+
+#[test]
+fn clocked_summary_matches_golden() {
+    let summary = fixture_analyzer().clocked_summary().unwrap();
+    assert_golden("habit_times.txt", &summary);
+}
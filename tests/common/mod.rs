@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+use chrono::NaiveDate;
+use std::fs;
+use std::path::PathBuf;
+
+use org_analyzer::analyze_stuff::Analyzer;
+use org_analyzer::config_stuff::{Config, CONFIG_FILE_NAME};
+use org_analyzer::data_stuff::HabitProgress;
+use org_analyzer::source_stuff::{HabitSource, OrgDirSource};
+
+pub fn fixture_notes() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/notes")
+}
+
+pub fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+pub fn fixture_progress() -> HabitProgress {
+    OrgDirSource::new(fixture_notes(), date("2025-01-01")).load_all()
+}
+
+pub fn fixture_config() -> Config {
+    Config::load(&fixture_notes().join(CONFIG_FILE_NAME)).unwrap()
+}
+
+pub fn fixture_analyzer() -> Analyzer {
+    Analyzer::from_progress(fixture_progress()).with_config(fixture_config())
+}
+
+/// Compares `actual` with `tests/golden/{name}`. Run with `UPDATE_GOLDEN=1` to rewrite the
+/// golden files after an intended change, then review the diff.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {e}, run with UPDATE_GOLDEN=1", path.display()));
+    assert!(
+        expected == actual,
+        "{} differs from the output, run with UPDATE_GOLDEN=1 and review the diff",
+        path.display()
+    );
+}
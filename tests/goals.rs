@@ -0,0 +1,65 @@
+mod common;
+
+use common::date;
+use org_analyzer::goal_stuff::{Direction, Goal, GoalProgress, GoalStatus, Trend};
+
+fn lose_to(target: f32) -> Goal {
+    Goal {
+        target,
+        deadline: None,
+        direction: Direction::Down,
+    }
+}
+
+#[test]
+fn projections_count_on_from_the_last_value() {
+    let points = [(date("2025-01-01"), 80.0), (date("2025-01-11"), 79.0)];
+    let trend = Trend::fit(&points).unwrap();
+    let last = date("2025-01-11");
+
+    assert_eq!(
+        trend.reaches(&lose_to(78.0), last, last),
+        Some(date("2025-01-21"))
+    );
+    // A line that already crossed the target or a stale trend doesn't land in the past
+    assert_eq!(trend.reaches(&lose_to(79.5), last, last), Some(last));
+    assert_eq!(
+        trend.reaches(&lose_to(78.0), last, date("2025-03-01")),
+        Some(date("2025-03-01"))
+    );
+    assert_eq!(trend.reaches(&lose_to(90.0), last, last), Some(last));
+}
+
+#[test]
+fn missing_projections_say_why() {
+    let progress = |slope: f32| {
+        let trend = Trend {
+            origin: date("2025-01-01"),
+            slope,
+            intercept: 80.0,
+        };
+        let goal = lose_to(78.0);
+        let projected = trend.reaches(&goal, date("2025-01-11"), date("2025-01-11"));
+        GoalProgress {
+            objective: "Weight=".to_string(),
+            goal,
+            current: Some(80.0),
+            trend: Some(trend),
+            projected,
+            status: GoalStatus::OffTrack,
+        }
+    };
+
+    let flat = progress(-1e-30);
+    assert_eq!(flat.projected, None);
+    assert_eq!(
+        flat.to_string(),
+        "Weight=: 80.0 -> 78.0, not within range, off track"
+    );
+    let rising = progress(0.1);
+    assert_eq!(rising.projected, None);
+    assert_eq!(
+        rising.to_string(),
+        "Weight=: 80.0 -> 78.0, trend heading the wrong way, off track"
+    );
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{date, fixture_progress};
+use org_analyzer::data_stuff::{ConflictRule, HabitDayPerformance, HabitProgress, Performance};
+use org_analyzer::import_stuff::Importer;
+use org_analyzer::source_stuff::HabitSource;
+use std::path::PathBuf;
+
+fn import_fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/import")
+        .join(name)
+}
+
+fn imported() -> HabitProgress {
+    let mut importer = Importer::new();
+    importer
+        .loop_checkmarks(&import_fixture("checkmarks.csv"))
+        .unwrap();
+    importer.habit_csv(&import_fixture("habits.csv")).unwrap();
+    importer
+        .scale_csv(&import_fixture("scale.csv"), "Weight=")
+        .unwrap();
+    importer.into_source().load_all()
+}
+
+fn day<'a>(progress: &'a HabitProgress, on: &str) -> &'a HabitDayPerformance {
+    progress
+        .hpds()
+        .iter()
+        .find(|hdp| hdp.date() == date(on))
+        .unwrap_or_else(|| panic!("no day {on}"))
+}
+
+fn habit(hdp: &HabitDayPerformance, title: &str) -> Option<Performance> {
+    hdp.todo_performance
+        .iter()
+        .find(|tp| tp.title() == title)
+        .map(|tp| tp.performance())
+}
+
+#[test]
+fn exports_are_read_with_or_without_headers() {
+    let progress = imported();
+    let dates: Vec<_> = progress.hpds().iter().map(|hdp| hdp.date()).collect();
+    assert_eq!(dates, [date("2025-03-03"), date("2025-03-10")]);
+
+    // Loop auto checks and skips are left out
+    let monday = day(&progress, "2025-03-03");
+    assert_eq!(habit(monday, "#1 Walk"), Some(Performance::Fail));
+    assert_eq!(habit(monday, "#2 Walk"), Some(Performance::Done));
+    assert_eq!(habit(monday, "Swim"), None);
+    assert_eq!(monday.objective_values("Weight="), [181.2]);
+
+    // The headerless habit export keeps its first row, a repeated row doesn't override it
+    let next = day(&progress, "2025-03-10");
+    assert_eq!(habit(next, "Stretch"), Some(Performance::Done));
+    assert_eq!(habit(next, "#2 Walk"), None);
+    assert_eq!(habit(next, "Swim"), Some(Performance::Fail));
+    assert_eq!(next.objective_values("Weight="), [82.0]);
+}
+
+#[test]
+fn conflicts_follow_the_rule() {
+    // Org has #1 Walk done, #2 Walk failed and 80.5 kg on 2025-03-03
+    let cases = [
+        (
+            ConflictRule::KeepOrg,
+            Performance::Done,
+            Performance::Fail,
+            80.5,
+        ),
+        (
+            ConflictRule::KeepImport,
+            Performance::Fail,
+            Performance::Done,
+            181.2,
+        ),
+        (
+            ConflictRule::AnyDone,
+            Performance::Done,
+            Performance::Done,
+            80.5,
+        ),
+    ];
+    for (rule, first, second, weight) in cases {
+        let mut progress = fixture_progress();
+        progress.merge(imported(), rule);
+
+        let monday = day(&progress, "2025-03-03");
+        assert_eq!(habit(monday, "#1 Walk"), Some(first), "{rule:?}");
+        assert_eq!(habit(monday, "#2 Walk"), Some(second), "{rule:?}");
+        assert_eq!(monday.objective_values("Weight="), [weight], "{rule:?}");
+        // Titles only one side has are kept whatever the rule
+        assert_eq!(habit(monday, "#1 Brush Teeth"), Some(Performance::Done));
+
+        // Days only the import has are added in order
+        let last = progress.hpds().last().unwrap();
+        assert_eq!(last.date(), date("2025-03-10"));
+        assert_eq!(habit(last, "Stretch"), Some(Performance::Done));
+    }
+    assert_eq!("any-done".parse(), Ok(ConflictRule::AnyDone));
+    assert!("newest".parse::<ConflictRule>().is_err());
+}
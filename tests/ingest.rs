@@ -0,0 +1,126 @@
+mod common;
+
+use common::{assert_golden, date, fixture_notes, fixture_progress};
+use org_analyzer::analyze_stuff::Analyzer;
+use org_analyzer::data_stuff::{process_org_file, Performance, Task, TaskPerformance};
+use org_analyzer::source_stuff::{HabitSource, MemorySource, OrgDirSource};
+
+#[test]
+fn progress_matches_golden() {
+    let progress = fixture_progress();
+    let json = serde_json::to_string_pretty(&progress).unwrap();
+    assert_golden("progress.json", &json);
+}
+
+#[test]
+fn missing_days_are_skipped() {
+    let progress = fixture_progress();
+    let dates: Vec<_> = progress.hpds().iter().map(|hdp| hdp.date()).collect();
+    assert_eq!(dates.len(), 37);
+    assert_eq!(dates.first(), Some(&date("2025-01-27")));
+    assert_eq!(dates.last(), Some(&date("2025-03-09")));
+    for missing in [
+        "2025-02-05",
+        "2025-02-12",
+        "2025-02-13",
+        "2025-02-14",
+        "2025-03-02",
+    ] {
+        assert!(!dates.contains(&date(missing)), "{missing} has no day file");
+    }
+}
+
+#[test]
+fn load_range_and_available_dates_agree() {
+    let source = OrgDirSource::new(fixture_notes(), date("2025-01-01"));
+    let february = source.load_range(date("2025-02-01"), date("2025-02-28"));
+    let available: Vec<_> = source
+        .available_dates()
+        .into_iter()
+        .filter(|d| d.format("%Y-%m").to_string() == "2025-02")
+        .collect();
+    let loaded: Vec<_> = february.hpds().iter().map(|hdp| hdp.date()).collect();
+    assert_eq!(loaded, available);
+    assert_eq!(loaded.len(), 24);
+}
+
+#[test]
+fn memory_source_feeds_the_analyzer() {
+    let march: Vec<_> = fixture_progress()
+        .hpds()
+        .iter()
+        .filter(|hdp| hdp.date() >= date("2025-03-01"))
+        .rev()
+        .cloned()
+        .collect();
+    let source = MemorySource::new(march);
+    let dates = source.available_dates();
+    assert_eq!(dates.first(), Some(&date("2025-03-01")));
+    assert_eq!(dates.last(), Some(&date("2025-03-09")));
+    assert_eq!(dates.len(), 8);
+    assert_eq!(
+        source
+            .load_range(date("2025-03-04"), date("2025-03-05"))
+            .hpds()
+            .len(),
+        2
+    );
+
+    let a = Analyzer::from_source(&source);
+    let weight = a.objective_series("weight").unwrap();
+    assert_eq!(weight.first(), Some(&(date("2025-03-01"), 80.4)));
+    assert_eq!(weight.len(), 7, "2025-03-06 has no weight");
+    let walk = a.habit_stats("#1 Walk");
+    assert_eq!((walk.done, walk.expected), (6, 8));
+}
+
+#[test]
+fn objectives_keep_decimals_and_parentheses() {
+    let progress = fixture_progress();
+    let day = &progress.hpds()[1];
+    assert_eq!(day.date(), date("2025-01-28"));
+    assert_eq!(day.objective("Weight="), Some(83.9));
+    assert_eq!(day.objective("GERD-Symptoms(0-10)="), Some(7.0));
+    assert_eq!(day.objective("Mood(0-10)="), Some(5.5));
+}
+
+#[test]
+fn empty_objective_slot_has_no_value() {
+    let progress = fixture_progress();
+    let day = progress
+        .hpds()
+        .iter()
+        .find(|hdp| hdp.date() == date("2025-02-02"))
+        .unwrap();
+    assert_eq!(day.objective("Weight="), None);
+    assert!(day.objective("Mood(0-10)=").is_some());
+}
+
+#[test]
+fn odd_titles_and_todos() {
+    let org = "\
+** DONE /HABIT/ Read (20 pages) & journal
+** TODO /HABIT/ #3 Walk
+** FAIL /HABIT/ #2 Brush Teeth
+** /OBJECTIVE/ Mood(0-10)=
+- 6.5
+";
+    let parsed = process_org_file(org.to_string());
+    assert_eq!(
+        parsed[0],
+        vec![
+            TaskPerformance::new(
+                Task::Todo("Read (20 pages) & journal".to_string()),
+                Performance::Done
+            ),
+            TaskPerformance::new(Task::Todo("#2 Brush Teeth".to_string()), Performance::Fail),
+        ]
+    );
+    assert_eq!(
+        parsed[1],
+        vec![TaskPerformance::new(
+            Task::Objective("Mood(0-10)=".to_string()),
+            Performance::Score(6.5)
+        )]
+    );
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use chrono::Days;
+use common::date;
+use org_analyzer::config_stuff::Config;
+use org_analyzer::data_stuff::{HabitDayPerformance, Performance, Task, TaskPerformance};
+use org_analyzer::schedule_stuff::{
+    habit_stats, habit_statuses, last_day, lifecycle, HabitStatus, Lifecycle, Schedule,
+};
+
+/// `days` days from 2025-03-01, with `title` done on the days `recorded` says
+fn history(days: u64, title: &str, recorded: impl Fn(u64) -> bool) -> Vec<HabitDayPerformance> {
+    (0..days)
+        .map(|i| {
+            let mut hdp = HabitDayPerformance::new(date("2025-03-01") + Days::new(i));
+            hdp.todo_performance.push(TaskPerformance::new(
+                Task::Todo("Walk".to_string()),
+                Performance::Done,
+            ));
+            if recorded(i) {
+                hdp.todo_performance.push(TaskPerformance::new(
+                    Task::Todo(title.to_string()),
+                    Performance::Done,
+                ));
+            }
+            hdp
+        })
+        .collect()
+}
+
+#[test]
+fn late_start_is_inactive_before_first_entry() {
+    let hdps = history(20, "Parasym", |i| i >= 10);
+    let life = lifecycle(&hdps, "Parasym", &Config::default());
+    assert_eq!(
+        life,
+        Lifecycle::Active {
+            start: Some(date("2025-03-11")),
+            retired: None
+        }
+    );
+
+    let statuses = habit_statuses(&hdps, "Parasym", &Schedule::Daily, life, last_day(&hdps));
+    assert_eq!(statuses[&date("2025-03-10")], HabitStatus::Inactive);
+    assert_eq!(statuses[&date("2025-03-11")], HabitStatus::Done);
+
+    let stats = habit_stats(&hdps, "Parasym", &Schedule::Daily, life, last_day(&hdps));
+    assert_eq!((stats.done, stats.expected), (10, 10));
+}
+
+#[test]
+fn long_gap_at_the_end_retires_the_habit() {
+    let hdps = history(60, "Sauna", |i| i < 20);
+    let life = lifecycle(&hdps, "Sauna", &Config::default());
+    assert_eq!(
+        life,
+        Lifecycle::Active {
+            start: Some(date("2025-03-01")),
+            retired: Some(date("2025-03-20"))
+        }
+    );
+
+    // A short gap is just unrecorded days
+    let hdps = history(30, "Sauna", |i| i < 20);
+    let life = lifecycle(&hdps, "Sauna", &Config::default());
+    let statuses = habit_statuses(&hdps, "Sauna", &Schedule::Daily, life, last_day(&hdps));
+    assert_eq!(statuses[&date("2025-03-30")], HabitStatus::Unrecorded);
+}
+
+#[test]
+fn config_dates_win_over_the_notes() {
+    let config: Config = toml::from_str(
+        r#"
+        [habits."Parasym"]
+        start = "2025-03-05"
+        retired = "2025-03-14"
+        "#,
+    )
+    .unwrap();
+    let hdps = history(20, "Parasym", |i| i >= 8);
+    let life = lifecycle(&hdps, "Parasym", &config);
+    let stats = habit_stats(&hdps, "Parasym", &Schedule::Daily, life, last_day(&hdps));
+    // 2025-03-05 to 2025-03-14, done from 2025-03-09
+    assert_eq!((stats.done, stats.expected), (6, 10));
+}
+
+#[test]
+fn never_recorded_habit_is_never_active() {
+    let hdps = history(10, "Parasym", |_| false);
+    let life = lifecycle(&hdps, "Parasym", &Config::default());
+    assert_eq!(life, Lifecycle::Never);
+    let stats = habit_stats(&hdps, "Parasym", &Schedule::Daily, life, last_day(&hdps));
+    assert_eq!(stats.to_string(), "Parasym: nothing expected");
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{assert_golden, fixture_config, fixture_notes};
+use org_analyzer::config_stuff::Config;
+use org_analyzer::lint_stuff::{lint_day, lint_notes, LintKind};
+use std::path::{Path, PathBuf};
+
+fn lint_fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lint_notes")
+}
+
+#[test]
+fn lint_report_matches_golden() {
+    let issues = lint_notes(&lint_fixture(), &Config::default()).unwrap();
+    let report: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+    assert_golden("lint.txt", &report.join("\n"));
+}
+
+#[test]
+fn typo_suggests_known_title() {
+    let issues = lint_notes(&lint_fixture(), &Config::default()).unwrap();
+    let suggestion = issues.iter().find_map(|i| match &i.kind {
+        LintKind::UnknownHabit { title, suggestion } if title == "#1 Wlak" => suggestion.clone(),
+        _ => None,
+    });
+    assert_eq!(suggestion.as_deref(), Some("#1 Walk"));
+}
+
+#[test]
+fn ingest_fixture_only_has_the_planted_problems() {
+    let issues = lint_notes(&fixture_notes(), &fixture_config()).unwrap();
+    assert!(issues.iter().all(|i| matches!(
+        &i.kind,
+        LintKind::UnknownHabit { title, .. } if title == "Read (20 pages) & journal"
+    ) || matches!(&i.kind, LintKind::MissingValue { objective } if objective == "Weight=")));
+}
+
+#[test]
+fn tagged_habits_are_known_by_their_title() {
+    let day = "** DONE /HABIT/ #1 Walk :outdoor:\n** FAIL /HABIT/ Swim :pool:travel:\n";
+    let issues = lint_day(Path::new("2025/March/14.org"), day, &Config::default());
+    assert_eq!(issues, []);
+}
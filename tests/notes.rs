@@ -0,0 +1,62 @@
+mod common;
+
+use common::{assert_golden, date, fixture_analyzer, fixture_progress};
+use org_analyzer::data_stuff::{process_day_notes, process_org_file};
+
+#[test]
+fn tags_and_notes_are_read_from_the_day() {
+    let org = "\
+#+TITLE: Friday, March 14 2025
+#+FILETAGS: :travel:sick:
+* Habits :Travel:weekend:
+** DONE /HABIT/ Walk :outdoor:
+* Notes
+** /NOTE/ Ate late :food:
+
+   Pizza at 23:00.
+** /NOTE/ Slept badly
+** /OBJECTIVE/ Weight=
+- 82.4
+";
+    let (tags, notes) = process_day_notes(org);
+    assert_eq!(tags, vec!["travel", "sick", "weekend", "food"]);
+    assert_eq!(notes, vec!["Ate late Pizza at 23:00.", "Slept badly"]);
+}
+
+#[test]
+fn habit_tags_stay_out_of_the_title() {
+    let org = "\
+** DONE /HABIT/ Walk :outdoor:
+** FAIL /HABIT/ Read: 20 pages :home:evening:
+** DONE /HABIT/ Stretch
+";
+    let parsed = process_org_file(org.to_string());
+    let titles: Vec<&str> = parsed[0].iter().map(|tp| tp.title()).collect();
+    assert_eq!(titles, ["Walk", "Read: 20 pages", "Stretch"]);
+    let (tags, _) = process_day_notes(org);
+    assert!(tags.is_empty());
+}
+
+#[test]
+fn fixture_days_carry_their_tags() {
+    let progress = fixture_progress();
+    let travel: Vec<_> = progress
+        .filter_by_tag("travel")
+        .iter()
+        .map(|hdp| hdp.date())
+        .collect();
+    assert_eq!(travel, vec![date("2025-03-04"), date("2025-03-05")]);
+
+    let march_5 = &progress.filter_by_tag("food")[0];
+    assert_eq!(
+        march_5.notes,
+        vec!["Hotel breakfast, ate late Dinner at 22:00 after the train."]
+    );
+}
+
+#[test]
+fn tagged_summary_matches_golden() {
+    let a = fixture_analyzer();
+    assert_golden("tagged_travel.txt", &a.tagged_summary("travel").unwrap());
+    assert!(a.tagged_summary("holiday").is_err());
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{assert_golden, date, fixture_analyzer};
+use org_analyzer::query_stuff::DayFilter;
+
+#[test]
+fn filters_parse_and_print() {
+    let filter: DayFilter = "travel, !sick,-work".parse().unwrap();
+    assert_eq!(filter.with, vec!["travel"]);
+    assert_eq!(filter.without, vec!["sick", "work"]);
+    assert_eq!(filter.to_string(), "travel,!sick,!work");
+    assert_eq!(filter.file_label(), "travel+no-sick+no-work");
+    assert!("".parse::<DayFilter>().unwrap().is_empty());
+    assert!("travel,!".parse::<DayFilter>().is_err());
+}
+
+#[test]
+fn filter_narrows_series_and_stats() {
+    let a = fixture_analyzer().with_filter("travel".parse().unwrap());
+    let dates: Vec<_> = a.days().iter().map(|hdp| hdp.date()).collect();
+    assert_eq!(dates, vec![date("2025-03-04"), date("2025-03-05")]);
+    assert!(a
+        .objective_series("weight")
+        .unwrap()
+        .iter()
+        .all(|(d, _)| dates.contains(d)));
+
+    let all = fixture_analyzer();
+    let rest = fixture_analyzer().with_filter("!travel".parse().unwrap());
+    let walk = |a: &org_analyzer::analyze_stuff::Analyzer| a.habit_stats("#1 Walk");
+    assert_eq!(walk(&a).done + walk(&rest).done, walk(&all).done);
+    assert_eq!(
+        walk(&a).expected + walk(&rest).expected,
+        walk(&all).expected
+    );
+}
+
+#[test]
+fn segment_summary_matches_golden() {
+    let a = fixture_analyzer();
+    assert_golden("segment_travel.txt", &a.segment_summary("travel").unwrap());
+    assert!(a.segment_summary("holiday").is_err());
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use chrono::NaiveTime;
+use common::date;
+use org_analyzer::analyze_stuff::Analyzer;
+use org_analyzer::config_stuff::Config;
+use org_analyzer::data_stuff::{process_org_file, Aggregate, HabitDayPerformance, HabitProgress};
+
+const DAY: &str = "\
+** DONE /HABIT/ Walk
+** /OBJECTIVE/ Weight=
+- 81.9 @ 21:00
+- 82.4 @ 07:30
+** /OBJECTIVE/ Mood(0-10)=
+- 6
+- 8
+- 4
+";
+
+fn day(d: &str, org: &str) -> HabitDayPerformance {
+    let mut parsed = process_org_file(org.to_string());
+    let mut hdp = HabitDayPerformance::new(date(d));
+    hdp.objective_performance = parsed.remove(1);
+    hdp.todo_performance = parsed.remove(0);
+    hdp
+}
+
+#[test]
+fn every_reading_is_kept_with_its_time() {
+    let hdp = day("2025-03-01", DAY);
+    let weights: Vec<_> = hdp
+        .objective_performance
+        .iter()
+        .filter(|tp| tp.title() == "Weight=")
+        .map(|tp| tp.time())
+        .collect();
+    assert_eq!(
+        weights,
+        vec![
+            NaiveTime::from_hms_opt(21, 0, 0),
+            NaiveTime::from_hms_opt(7, 30, 0)
+        ]
+    );
+    // Readings come back in time order, so first is the morning weight
+    assert_eq!(hdp.objective_values("Weight="), vec![82.4, 81.9]);
+    assert_eq!(hdp.objective("Weight="), Some(82.4));
+    assert_eq!(hdp.objective_values("Mood(0-10)="), vec![6.0, 8.0, 4.0]);
+}
+
+#[test]
+fn aggregation_rules() {
+    let hdp = day("2025-03-01", DAY);
+    let mood = |aggregate| hdp.objective_by("Mood(0-10)=", aggregate);
+    assert_eq!(mood(Aggregate::First), Some(6.0));
+    assert_eq!(mood(Aggregate::Last), Some(4.0));
+    assert_eq!(mood(Aggregate::Mean), Some(6.0));
+    assert_eq!(mood(Aggregate::Min), Some(4.0));
+    assert_eq!(mood(Aggregate::Max), Some(8.0));
+    assert_eq!(mood(Aggregate::Sum), Some(18.0));
+    assert_eq!(hdp.objective_by("Weight", Aggregate::Mean), None);
+}
+
+#[test]
+fn configured_aggregate_drives_the_series() {
+    let progress = HabitProgress(vec![
+        day("2025-03-01", DAY),
+        day("2025-03-02", "** /OBJECTIVE/ mood(0-10)=\n- 2\n- 3\n"),
+    ]);
+    let config: Config = toml::from_str(
+        r#"
+        [objectives."Mood(0-10)="]
+        aggregate = "max"
+        "#,
+    )
+    .unwrap();
+    let a = Analyzer::from_progress(progress).with_config(config);
+
+    let series = a.objective_series("mood").unwrap();
+    assert_eq!(
+        series,
+        vec![(date("2025-03-01"), 8.0), (date("2025-03-02"), 3.0)]
+    );
+    // Renaming onto the canonical title keeps both readings
+    assert_eq!(
+        a.progress().hpds()[1].objective_values("Mood(0-10)="),
+        vec![2.0, 3.0]
+    );
+}
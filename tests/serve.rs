@@ -0,0 +1,106 @@
+mod common;
+
+use common::{fixture_analyzer, fixture_progress};
+use org_analyzer::analyze_stuff::Analyzer;
+use org_analyzer::config_stuff::Config;
+use org_analyzer::serve_stuff::{apply_query, route, Reply};
+use serde_json::Value;
+
+fn get(a: &mut Analyzer, url: &str) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    match apply_query(a, query) {
+        Ok(()) => route(a, path),
+        Err(e) => panic!("{url}: {e}"),
+    }
+}
+
+fn get_json(a: &mut Analyzer, url: &str) -> (u16, Value) {
+    let reply = get(a, url);
+    assert_eq!(reply.content_type, "application/json", "{url}");
+    (reply.status, serde_json::from_slice(&reply.body).unwrap())
+}
+
+#[test]
+fn days_are_listed_and_looked_up() {
+    let mut a = fixture_analyzer();
+    let (status, days) = get_json(&mut a, "/days");
+    assert_eq!(status, 200);
+    assert_eq!(days.as_array().unwrap().len(), 37);
+
+    let (status, day) = get_json(&mut a, "/days/2025-03-04");
+    assert_eq!(status, 200);
+    assert_eq!(day["habits"]["#1 Walk"], "done");
+    assert_eq!(day["objectives"]["Weight="], 80.1);
+    assert_eq!(day["tags"], serde_json::json!(["travel"]));
+}
+
+#[test]
+fn days_follow_the_only_filter() {
+    let mut a = fixture_analyzer();
+    let (_, days) = get_json(&mut a, "/days?only=travel");
+    let dates: Vec<_> = days
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|day| day["date"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(dates, ["2025-03-04", "2025-03-05"]);
+
+    assert_eq!(get_json(&mut a, "/days/2025-03-04?only=travel").0, 200);
+    assert_eq!(get_json(&mut a, "/days/2025-03-04?only=!travel").0, 404);
+}
+
+#[test]
+fn charts_render_as_svg_and_png() {
+    let mut a = fixture_analyzer();
+    let reply = get(&mut a, "/charts/habits/2025-February.svg");
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.content_type, "image/svg+xml");
+    assert!(String::from_utf8(reply.body).unwrap().starts_with("<svg"));
+
+    let reply = get(&mut a, "/charts/objectives/weight/2025-W10.png");
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.content_type, "image/png");
+    assert_eq!(&reply.body[1..4], b"PNG");
+
+    let reply = get(&mut a, "/charts/habits/2025-03-01_2025-03-09.svg");
+    assert_eq!(reply.status, 200);
+}
+
+#[test]
+fn bad_requests_get_error_statuses() {
+    let mut a = fixture_analyzer();
+    for (url, status) in [
+        ("/days/yesterday", 400),
+        ("/charts/habits/2025-February.gif", 400),
+        ("/charts/habits/2025-February", 400),
+        ("/days/2025-02-05", 404),
+        ("/habits/Walk/stats", 404),
+        ("/objectives/height/series", 404),
+        ("/charts/habits/1999.svg", 404),
+        ("/charts/habits/2025-03-10_2025-03-01.svg", 404),
+        ("/charts/habits/2025-01-01_2035-01-01.png", 404),
+        ("/nowhere", 404),
+    ] {
+        let (actual, body) = get_json(&mut a, url);
+        assert_eq!(actual, status, "{url}: {body}");
+        assert!(body["error"].is_string(), "{url}");
+    }
+}
+
+#[test]
+fn habit_stats_follow_aliases_and_case() {
+    let config: Config = toml::from_str(
+        r##"
+        [aliases]
+        "Evening Walk" = "#3 Walk"
+        "##,
+    )
+    .unwrap();
+    let mut a = Analyzer::from_progress(fixture_progress()).with_config(config);
+    for url in ["/habits/%233%20walk/stats", "/habits/Evening%20Walk/stats"] {
+        let (status, stats) = get_json(&mut a, url);
+        assert_eq!(status, 200, "{url}");
+        assert_eq!(stats["title"], "#3 Walk", "{url}");
+    }
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use chrono::Datelike;
+use common::{assert_golden, date, fixture_analyzer, fixture_progress};
+use org_analyzer::schedule_stuff::{
+    habit_stats, habit_statuses, last_day, HabitStatus, Lifecycle, Schedule,
+};
+
+#[test]
+fn habit_completion_matches_golden() {
+    let completion = fixture_analyzer().habit_completion().unwrap();
+    assert_golden("habit_completion.txt", &completion);
+}
+
+#[test]
+fn goal_summary_matches_golden() {
+    let summary = fixture_analyzer().goal_summary().unwrap();
+    assert_golden("goal_summary.txt", &summary);
+}
+
+#[test]
+fn habit_stats_follow_schedules() {
+    let a = fixture_analyzer();
+    let walk = a.habit_stats("#1 Walk");
+    assert_eq!((walk.done, walk.expected), (30, 37));
+
+    // Sauna is only expected at the weekend, 12 weekend days less the missing 2025-03-02
+    let sauna = a.habit_stats("Sauna");
+    assert_eq!(sauna.expected, 11);
+}
+
+#[test]
+fn weeks_the_notes_stop_in_are_still_running() {
+    let progress = fixture_progress();
+    let hdps: Vec<_> = progress
+        .hpds()
+        .iter()
+        .filter(|hdp| hdp.date() <= date("2025-03-03"))
+        .cloned()
+        .collect();
+    let swim = Schedule::PerWeek { times: 2 };
+    let always = Lifecycle::Active {
+        start: None,
+        retired: None,
+    };
+
+    // Monday of a week with no swim yet, it can still be made up later in the week
+    let as_of = date("2025-03-03");
+    let statuses = habit_statuses(&hdps, "Swim", &swim, always, as_of);
+    assert_eq!(statuses[&date("2025-03-03")], HabitStatus::NotScheduled);
+    let running = habit_stats(&hdps, "Swim", &swim, always, as_of);
+    let before = habit_stats(&hdps[..hdps.len() - 1], "Swim", &swim, always, as_of);
+    assert_eq!(running, before);
+}
+
+#[test]
+fn short_weeks_only_miss_the_shortfall() {
+    let progress = fixture_progress();
+    let hdps = progress.hpds();
+    let swim = Schedule::PerWeek { times: 2 };
+    let always = Lifecycle::Active {
+        start: None,
+        retired: None,
+    };
+
+    // 2025-W07 has day files for Mon, Tue, Sat and Sun and one swim on Tuesday
+    let statuses = habit_statuses(hdps, "Swim", &swim, always, last_day(hdps));
+    let week: Vec<HabitStatus> = ["2025-02-10", "2025-02-11", "2025-02-15", "2025-02-16"]
+        .iter()
+        .map(|d| statuses[&date(d)])
+        .collect();
+    assert_eq!(
+        week,
+        [
+            HabitStatus::Unrecorded,
+            HabitStatus::Done,
+            HabitStatus::NotScheduled,
+            HabitStatus::NotScheduled,
+        ]
+    );
+
+    let w07: Vec<_> = hdps
+        .iter()
+        .filter(|hdp| hdp.date().iso_week() == date("2025-02-10").iso_week())
+        .cloned()
+        .collect();
+    let stats = habit_stats(&w07, "Swim", &swim, always, last_day(hdps));
+    assert_eq!((stats.done, stats.expected), (1, 2));
+}
+
+#[test]
+fn past_weeks_are_over_whatever_slice_is_scored() {
+    let progress = fixture_progress();
+    let hdps = progress.hpds();
+    // 2025-W09 has no file for its Sunday, 2025-03-02, and three swims in six days
+    let w09: Vec<_> = hdps
+        .iter()
+        .filter(|hdp| hdp.date().iso_week() == date("2025-02-24").iso_week())
+        .cloned()
+        .collect();
+    let five = Schedule::PerWeek { times: 5 };
+    let always = Lifecycle::Active {
+        start: None,
+        retired: None,
+    };
+
+    let stats = habit_stats(&w09, "Swim", &five, always, last_day(hdps));
+    assert_eq!((stats.done, stats.expected), (3, 5));
+    let statuses = habit_statuses(&w09, "Swim", &five, always, last_day(hdps));
+    let missed = statuses
+        .values()
+        .filter(|s| matches!(s, HabitStatus::Missed | HabitStatus::Unrecorded))
+        .count();
+    assert_eq!(missed, 2);
+
+    // Seen from its own last day the week could still be made up
+    let stats = habit_stats(&w09, "Swim", &five, always, last_day(&w09));
+    assert_eq!((stats.done, stats.expected), (3, 3));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::{assert_golden, fixture_analyzer};
+use org_analyzer::config_stuff::Config;
+use org_analyzer::draw_stuff::heatmap::wrap;
+use org_analyzer::theme_stuff::{Colour, PaletteName, Theme};
+use plotters::prelude::*;
+
+#[test]
+fn theme_is_read_from_the_config() {
+    let config: Config = toml::from_str(
+        r##"
+        [theme]
+        palette = "colour_blind"
+        font = "DejaVu Sans"
+        heatmap_size = [1400, 900]
+        label_length = 0
+        colours = { done = "#1B9E77" }
+        "##,
+    )
+    .unwrap();
+    let theme = &config.theme;
+    assert_eq!(theme.palette, PaletteName::ColourBlind);
+    assert_eq!(theme.heatmap_size, Some((1400, 900)));
+    assert_eq!(theme.objective_size, Theme::default().objective_size);
+
+    let colours = theme.colours();
+    assert_eq!(colours.done, RGBColor(0x1b, 0x9e, 0x77));
+    assert_eq!(colours.missed, PaletteName::ColourBlind.palette().missed);
+    assert_eq!(Colour(0x1b, 0x9e, 0x77).to_string(), "#1b9e77");
+
+    assert!(toml::from_str::<Config>("[theme]\ncolours = { done = \"green\" }").is_err());
+    assert!(toml::from_str::<Config>("[theme]\ncolours = { dne = \"#000000\" }").is_err());
+    assert!("sepia".parse::<PaletteName>().is_err());
+}
+
+#[test]
+fn palettes_are_written_the_way_the_cli_takes_them() {
+    let mut config: Config = toml::from_str("[theme]\npalette = \"colour-blind\"").unwrap();
+    assert_eq!(config.theme.palette, PaletteName::ColourBlind);
+    assert_eq!("colour-blind".parse(), Ok(PaletteName::ColourBlind));
+
+    for palette in [
+        PaletteName::Light,
+        PaletteName::ColourBlind,
+        PaletteName::Dark,
+    ] {
+        config.theme.palette = palette;
+        let written = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&written).unwrap(), config);
+    }
+    assert!(toml::to_string(&config.theme)
+        .unwrap()
+        .contains("palette = \"dark\""));
+    config.theme.palette = PaletteName::ColourBlind;
+    assert!(toml::to_string(&config.theme)
+        .unwrap()
+        .contains("palette = \"colour-blind\""));
+}
+
+#[test]
+fn labels_are_cut_to_the_theme_length() {
+    let mut theme = Theme {
+        label_length: 6,
+        ..Theme::default()
+    };
+    assert_eq!(theme.label("Anki Ukrainian Lesson"), "Anki U...");
+    assert_eq!(theme.label("Swim"), "Swim");
+    theme.label_length = 0;
+    assert_eq!(
+        theme.label("Anki Ukrainian Lesson"),
+        "Anki Ukrainian Lesson"
+    );
+}
+
+#[test]
+fn dark_heatmap_with_rotated_labels_matches_golden() {
+    let theme = Theme {
+        palette: PaletteName::Dark,
+        label_length: 0,
+        rotate_labels: true,
+        cell_borders: false,
+        ..Theme::default()
+    };
+    let a = fixture_analyzer().with_theme(theme);
+    let frame = a.frame_by_label("2025-W10").unwrap();
+    let mut svg = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut svg, a.heatmap_size(&frame, false)).into_drawing_area();
+        a.draw_habits_on(&root, &frame, false);
+    }
+    assert_golden("charts/habits-dark-2025-W10.svg", &svg);
+}
+
+#[test]
+fn titles_wrap_at_spaces() {
+    assert_eq!(
+        wrap("Anki Ukrainian Lesson", 8),
+        ["Anki", "Ukrainia", "n", "Lesson"]
+    );
+    assert_eq!(
+        wrap("Anki Ukrainian Lesson", 9),
+        ["Anki", "Ukrainian", "Lesson"]
+    );
+    assert_eq!(wrap("Swim", 8), ["Swim"]);
+    assert_eq!(
+        wrap("Sauna Parasympathetic Swim", 8),
+        ["Sauna", "Parasymp", "athetic", "Swim"]
+    );
+    assert_eq!(wrap("", 8), [""]);
+}
+
+#[test]
+fn heatmap_grows_with_the_days_and_habits() {
+    let a = fixture_analyzer();
+    let week = a.frame_by_label("2025-W10").unwrap();
+    let year = a.frame_by_label("2025").unwrap();
+    let (week_width, week_height) = a.heatmap_size(&week, false);
+    let (year_width, year_height) = a.heatmap_size(&year, false);
+    assert_eq!(week_width, year_width);
+    let extra_days = year.inner().days() - week.inner().days();
+    assert_eq!(
+        year_height - week_height,
+        extra_days * Theme::default().cell_size.1
+    );
+    assert!(a.heatmap_size(&week, true).0 < week_width);
+
+    let fixed = fixture_analyzer().with_theme(Theme {
+        heatmap_size: Some((1000, 1000)),
+        ..Theme::default()
+    });
+    assert_eq!(fixed.heatmap_size(&year, false), (1000, 1000));
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::date;
+use org_analyzer::analyze_stuff::Analyzer;
+use org_analyzer::config_stuff::Config;
+use org_analyzer::data_stuff::{process_org_file, HabitDayPerformance, HabitProgress, Performance};
+use org_analyzer::unit_stuff::{parse_value, Quantity, Unit};
+
+fn progress(days: &[(&str, &str)]) -> HabitProgress {
+    HabitProgress(
+        days.iter()
+            .map(|(d, org)| {
+                let mut hdp = HabitDayPerformance::new(date(d));
+                hdp.objective_performance = process_org_file(org.to_string()).remove(1);
+                hdp
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn values_keep_decimals_signs_and_units() {
+    assert_eq!(parse_value("82.45 kg"), Some((82.45, Some(Unit::Kg))));
+    assert_eq!(parse_value("181lb"), Some((181.0, Some(Unit::Lb))));
+    assert_eq!(parse_value("-1.5"), Some((-1.5, None)));
+    assert_eq!(parse_value("36.6 °C"), Some((36.6, Some(Unit::Celsius))));
+    assert_eq!(
+        parse_value("120 mmHg"),
+        Some((120.0, Some(Unit::Other("mmHg".to_string()))))
+    );
+    assert_eq!(parse_value("82,5"), None);
+    assert_eq!(parse_value("n/a"), None);
+
+    let org = "** /OBJECTIVE/ Weight=\n- 82.45 kg\n** /OBJECTIVE/ Mood(0-10)=\n- -2\n";
+    let objectives = &process_org_file(org.to_string())[1];
+    assert_eq!(
+        objectives[0].performance(),
+        Performance::Measured(Quantity {
+            value: 82.45,
+            unit: Unit::Kg
+        })
+    );
+    assert_eq!(objectives[1].performance(), Performance::Score(-2.0));
+}
+
+#[test]
+fn quantities_convert_between_units() {
+    let weight = Quantity {
+        value: 100.0,
+        unit: Unit::Lb,
+    };
+    let kg = weight.convert(&Unit::Kg).unwrap();
+    assert!((kg.value - 45.359_24).abs() < 1e-3);
+    let back = kg.convert(&Unit::Lb).unwrap();
+    assert!((back.value - 100.0).abs() < 1e-3);
+
+    let fever = Quantity {
+        value: 38.0,
+        unit: Unit::Celsius,
+    };
+    assert!((fever.convert(&Unit::Fahrenheit).unwrap().value - 100.4).abs() < 1e-3);
+    assert_eq!(fever.convert(&Unit::Kg), None);
+}
+
+#[test]
+fn weights_are_shown_in_the_configured_unit() {
+    let days = progress(&[
+        ("2025-03-01", "** /OBJECTIVE/ Weight=\n- 82.0\n"),
+        ("2025-03-02", "** /OBJECTIVE/ Weight=\n- 181 lb\n"),
+    ]);
+    let config: Config = toml::from_str("[objectives.\"Weight=\"]\nunit = \"lb\"\n").unwrap();
+    let a = Analyzer::from_progress(days.clone()).with_config(config);
+
+    let series = a.objective_series("weight").unwrap();
+    assert!((series[0].1 - 180.779).abs() < 1e-2, "{series:?}");
+    assert!((series[1].1 - 181.0).abs() < 1e-3, "{series:?}");
+
+    // Without a configured unit weights are shown in kg, bare numbers already being kg
+    let a = Analyzer::from_progress(days).with_config(Config::default());
+    let series = a.objective_series("weight").unwrap();
+    assert!((series[0].1 - 82.0).abs() < 1e-3, "{series:?}");
+    assert!((series[1].1 - 82.100).abs() < 1e-2, "{series:?}");
+}
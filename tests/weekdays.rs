@@ -0,0 +1,72 @@
+mod common;
+
+use chrono::Weekday;
+use common::{assert_golden, date, fixture_analyzer, fixture_progress};
+use org_analyzer::data_stuff::Aggregate;
+use org_analyzer::schedule_stuff::HabitStatus;
+use org_analyzer::weekday_stuff::{weekday_rates, weekday_values, WeekdayRates};
+use std::collections::BTreeMap;
+
+#[test]
+fn statuses_count_by_weekday() {
+    // 2025-03-03 is a Monday
+    let statuses: BTreeMap<_, _> = [
+        ("2025-03-03", HabitStatus::Done),
+        ("2025-03-04", HabitStatus::Missed),
+        ("2025-03-08", HabitStatus::Unrecorded),
+        ("2025-03-09", HabitStatus::NotScheduled),
+        ("2025-03-10", HabitStatus::Done),
+        ("2025-03-11", HabitStatus::Inactive),
+        ("2025-03-15", HabitStatus::Partial(1, 2)),
+    ]
+    .into_iter()
+    .map(|(d, s)| (date(d), s))
+    .collect();
+    let rates = weekday_rates("Walk", &statuses);
+    assert_eq!(rates.done, [2, 0, 0, 0, 0, 1, 0]);
+    assert_eq!(rates.expected, [2, 1, 0, 0, 0, 3, 0]);
+    assert_eq!(rates.rate(Weekday::Sun), None);
+    assert_eq!(rates.best(), Some(Weekday::Mon));
+    assert_eq!(rates.worst(), Some(Weekday::Tue));
+    assert_eq!(rates.weekend(), (Some(2.0 / 3.0), Some(1.0 / 3.0)));
+
+    let total = WeekdayRates::total("both", [&rates, &rates]);
+    assert_eq!(total.expected, [4, 2, 0, 0, 0, 6, 0]);
+    assert_eq!(
+        total.to_string(),
+        "both: best Mon 100% (4/4), worst Tue 0% (0/2)"
+    );
+}
+
+#[test]
+fn objective_values_by_weekday() {
+    let progress = fixture_progress();
+    let values = weekday_values(progress.hpds(), "Mood(0-10)=", Aggregate::First);
+    let mood_days = progress
+        .hpds()
+        .iter()
+        .filter(|hdp| hdp.objective("Mood(0-10)=").is_some())
+        .count();
+    assert_eq!(values.values.iter().map(Vec::len).sum::<usize>(), mood_days);
+    // 2025-03-05 is a Wednesday
+    let march_5 = progress
+        .hpds()
+        .iter()
+        .find(|hdp| hdp.date() == date("2025-03-05"));
+    let mood = march_5.and_then(|hdp| hdp.objective("Mood(0-10)="));
+    assert!(values.values[2].contains(&mood.unwrap()));
+    let highest = values.highest().unwrap();
+    let lowest = values.lowest().unwrap();
+    assert!(values.mean(highest) >= values.mean(lowest));
+    assert!(weekday_values(progress.hpds(), "Sleep=", Aggregate::First)
+        .highest()
+        .is_none());
+}
+
+#[test]
+fn weekday_summary_matches_golden() {
+    assert_golden(
+        "weekday_summary.txt",
+        &fixture_analyzer().weekday_summary().unwrap(),
+    );
+}
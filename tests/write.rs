@@ -0,0 +1,77 @@
+mod common;
+
+use common::{date, fixture_progress};
+use org_analyzer::analyze_stuff::Analyzer;
+use org_analyzer::config_stuff::Config;
+use org_analyzer::data_stuff::{process_org_file, HabitProgress, Performance};
+use org_analyzer::write_stuff::{day_template, write_day_file};
+use std::fs;
+
+#[test]
+fn new_day_files_read_back_with_their_titles() {
+    let habits = HabitProgress::ordered_titles();
+    let objectives = ["Weight=", "GERD-Symptoms(0-10)=", "Mood(0-10)="];
+    let day = date("2025-03-10");
+
+    let root = std::env::temp_dir().join(format!("day-files-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let path = write_day_file(&root, day, &habits, &objectives).unwrap();
+    assert!(path.ends_with("2025/March/10.org"), "{}", path.display());
+    assert!(write_day_file(&root, day, &habits, &objectives).is_err());
+    let written = fs::read_to_string(&path).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(written, day_template(day, &habits, &objectives));
+
+    // Untouched TODOs and empty slots aren't data yet, so fill the day in first
+    let parsed = process_org_file(written.clone());
+    assert!(parsed[0].is_empty());
+    let filled = written
+        .replacen("** TODO", "** FAIL", 1)
+        .replace("** TODO", "** DONE")
+        .replace("- \n", "- 5\n");
+    let parsed = process_org_file(filled);
+    let titles =
+        |i: usize| -> Vec<String> { parsed[i].iter().map(|tp| tp.title().to_string()).collect() };
+    assert_eq!(titles(0), habits);
+    assert_eq!(titles(1), objectives);
+    assert_eq!(parsed[0][0].performance(), Performance::Fail);
+    assert_eq!(parsed[1][2].performance(), Performance::Score(5.0));
+}
+
+#[test]
+fn new_days_list_the_habits_active_on_them() {
+    let config: Config = toml::from_str(
+        r##"
+        [habits."Cold Shower"]
+        start = "2025-03-01"
+
+        [habits."Parasym"]
+        start = "2025-04-01"
+
+        [habits."Sauna"]
+        retired = "2025-03-05"
+        "##,
+    )
+    .unwrap();
+    let a = Analyzer::from_progress(fixture_progress()).with_config(config);
+    assert_eq!(
+        a.day_habits(date("2025-03-10")),
+        [
+            "#1 Brush Teeth",
+            "#1 Walk",
+            "#2 Walk",
+            "Swim",
+            "#3 Walk",
+            "#2 Brush Teeth",
+            "Anki Ukrainian Lesson",
+            "Cold Shower",
+        ]
+    );
+    assert!(a.day_habits(date("2025-04-02")).contains(&"Parasym"));
+
+    let empty = Analyzer::from_progress(HabitProgress(vec![]));
+    assert_eq!(
+        empty.day_habits(date("2025-03-10")),
+        HabitProgress::ordered_titles()
+    );
+}